@@ -0,0 +1,440 @@
+//! `iota-identity`: the identity operations of the backend without the web server.
+//!
+//! Identities are stored in the working directory under their name, like the backend's
+//! (`<name>_doc.json`, `<name>.stronghold`, ...). Every command prints a JSON object to stdout;
+//! failures print `{"error": ...}` to stderr and exit with status 1.
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use identity_iota::{
+    core::{Duration, Object, Timestamp, Url},
+    credential::{Credential, CredentialBuilder, Jwt, JwtPresentationOptions, Subject, SubjectHolderRelationship},
+    did::{CoreDID, DID},
+    document::Service,
+    iota::IotaDocument,
+};
+use identity_logic::{
+    deactivate_did, get_controller_client,
+    inspect::{inspect_jwt, JwtKind},
+    issuance::{build_credential, sign_credential},
+    presentation::create_presentation,
+    publish_did_update,
+    store::{create_or_load_identity, load_controller_key, load_existing_did, open_vault, save_did_document, IdentityFiles},
+    verification::Verifier,
+};
+use serde_json::{json, Value};
+use std::{
+    io::Read,
+    path::Path,
+    process::ExitCode,
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "iota-identity", about = "Create DIDs and issue, present and verify credentials on IOTA")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Manage the DIDs stored in the working directory
+    #[command(subcommand)]
+    Did(DidCommand),
+    /// Issue credentials
+    #[command(subcommand)]
+    Vc(VcCommand),
+    /// Create presentations
+    #[command(subcommand)]
+    Vp(VpCommand),
+    /// Verify a presentation (and the credentials in it) or a single credential
+    Verify(VerifyArgs),
+    /// Inspect JWTs
+    #[command(subcommand)]
+    Jwt(JwtCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum DidCommand {
+    /// Create the DID `name`, or load it if it already exists
+    Create { name: String },
+    /// Show a stored DID document, or resolve a DID from the network
+    Show {
+        /// Name of a stored identity, or a DID
+        identity: String,
+        /// Resolve the latest published version of a stored identity (not allowed with a DID)
+        #[arg(long)]
+        resolve: bool,
+    },
+    /// Publish a new version of a stored DID
+    Update(UpdateArgs),
+    /// Deactivate a stored DID
+    Deactivate { name: String },
+}
+
+#[derive(Debug, Args)]
+struct UpdateArgs {
+    name: String,
+    /// Fragment of a service to add, e.g. `linked-domain`
+    #[arg(long, requires_all = ["service_type", "service_endpoint"])]
+    add_service: Option<String>,
+    #[arg(long)]
+    service_type: Option<String>,
+    #[arg(long)]
+    service_endpoint: Option<String>,
+    /// Fragment of a service to remove
+    #[arg(long)]
+    remove_service: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+enum VcCommand {
+    /// Sign a credential for the subject in a JSON file
+    Issue {
+        /// Name of the issuing identity
+        #[arg(long)]
+        issuer: String,
+        /// JSON file with the credential subject; its `id` is the subject DID
+        #[arg(long)]
+        subject: String,
+        /// Credential type, e.g. `UniversityDegreeCredential`
+        #[arg(long = "type")]
+        credential_type: String,
+        /// Credential ID; defaults to a new URN
+        #[arg(long)]
+        id: Option<String>,
+        #[arg(long)]
+        validity_days: Option<i64>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum VpCommand {
+    /// Sign a presentation of one or more credentials
+    Create {
+        /// Name of the presenting identity
+        #[arg(long)]
+        holder: String,
+        /// Credential JWT, or a file containing one; repeat for several
+        #[arg(long = "vc", required = true)]
+        credentials: Vec<String>,
+        /// Nonce the verifier expects
+        #[arg(long)]
+        challenge: String,
+        #[arg(long, default_value_t = 10)]
+        expires_in_minutes: i64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum HolderBinding {
+    AlwaysSubject,
+    SubjectOnNonTransferable,
+    Any,
+}
+
+impl From<HolderBinding> for SubjectHolderRelationship {
+    fn from(binding: HolderBinding) -> Self {
+        match binding {
+            HolderBinding::AlwaysSubject => SubjectHolderRelationship::AlwaysSubject,
+            HolderBinding::SubjectOnNonTransferable => SubjectHolderRelationship::SubjectOnNonTransferable,
+            HolderBinding::Any => SubjectHolderRelationship::Any,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct VerifyArgs {
+    /// JWT, a file containing one, or `-` for stdin
+    jwt: String,
+    /// Nonce the presentation must carry
+    #[arg(long)]
+    challenge: Option<String>,
+    /// How credential subjects must relate to the presenting holder
+    #[arg(long, value_enum, default_value_t = HolderBinding::AlwaysSubject)]
+    holder_binding: HolderBinding,
+}
+
+#[derive(Debug, Subcommand)]
+enum JwtCommand {
+    /// Print the header and claims of a JWT without verifying it, with any structural problems
+    Decode {
+        /// JWT, a file containing one, or `-` for stdin
+        jwt: String,
+    },
+}
+
+// Reads `-` from stdin and existing paths from disk; anything else is the value itself
+fn read_input(value: &str) -> Result<String> {
+    let contents = if value == "-" {
+        let mut contents = String::new();
+        std::io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else if Path::new(value).is_file() {
+        std::fs::read_to_string(value).with_context(|| format!("cannot read {}", value))?
+    } else {
+        value.to_string()
+    };
+    Ok(contents.trim().to_string())
+}
+
+fn to_core_did(doc: &IotaDocument) -> Result<CoreDID> {
+    CoreDID::parse(doc.id().as_str()).context("invalid DID")
+}
+
+// --- DIDs ---
+
+async fn did_create(name: &str) -> Result<Value> {
+    let files = IdentityFiles::named(name)?;
+    let (doc, fragment) = create_or_load_identity(&files).await?;
+    Ok(json!({
+        "name": name,
+        "did": doc.id().to_string(),
+        "fragment": fragment,
+        "docFile": files.doc_file,
+    }))
+}
+
+async fn did_show(identity: &str, resolve: bool) -> Result<Value> {
+    let doc = if identity.starts_with("did:") {
+        if resolve {
+            bail!("--resolve applies to stored identities; a DID is always resolved from the network");
+        }
+        let did = CoreDID::parse(identity).with_context(|| format!("invalid DID {}", identity))?;
+        let verifier = Verifier::new();
+        if did.method() != "iota" {
+            // did:key, did:jwk and did:web documents carry no metadata
+            let doc = verifier.resolve(&did).await?;
+            return Ok(json!({"did": doc.id().to_string(), "document": doc}));
+        }
+        verifier.resolve_iota(&did).await?
+    } else {
+        let files = IdentityFiles::named(identity)?;
+        let stored = load_existing_did(&files.doc_file, identity)?;
+        match resolve {
+            true => Verifier::new().resolve_iota(&to_core_did(&stored)?).await?,
+            false => stored,
+        }
+    };
+    Ok(json!({
+        "did": doc.id().to_string(),
+        "deactivated": doc.metadata.deactivated.unwrap_or(false),
+        "document": doc,
+    }))
+}
+
+async fn did_update(args: &UpdateArgs) -> Result<Value> {
+    if args.add_service.is_none() && args.remove_service.is_none() {
+        bail!("nothing to update: pass --add-service or --remove-service");
+    }
+    let files = IdentityFiles::named(&args.name)?;
+    let stored = load_existing_did(&files.doc_file, &args.name)?;
+    let vault = open_vault(&files.stronghold_path).await?;
+    let client = get_controller_client(&vault.storage, &load_controller_key(&files.doc_file)?).await?;
+
+    // Start from the latest published version rather than the local copy
+    let mut document = client.resolve_did(stored.id()).await.context("cannot resolve the published DID document")?;
+
+    if let Some(fragment) = &args.remove_service {
+        let service_url = document.id().to_url().join(format!("#{}", fragment))?;
+        document
+            .remove_service(&service_url)
+            .with_context(|| format!("the DID document has no service {}", service_url))?;
+    }
+    if let Some(fragment) = &args.add_service {
+        let service_url = document.id().to_url().join(format!("#{}", fragment))?;
+        let endpoint = args.service_endpoint.as_deref().unwrap_or_default();
+        let service = Service::builder(Object::new())
+            .id(service_url)
+            .type_(args.service_type.clone().unwrap_or_default())
+            .service_endpoint(Url::parse(endpoint).with_context(|| format!("invalid service endpoint {}", endpoint))?)
+            .build()?;
+        document.insert_service(service)?;
+    }
+
+    let published = publish_did_update(&client, document).await?;
+    save_did_document(&files.doc_file, &published)?;
+    Ok(json!({
+        "did": published.id().to_string(),
+        "document": published,
+    }))
+}
+
+async fn did_deactivate(name: &str) -> Result<Value> {
+    let files = IdentityFiles::named(name)?;
+    let stored = load_existing_did(&files.doc_file, name)?;
+    let vault = open_vault(&files.stronghold_path).await?;
+    let client = get_controller_client(&vault.storage, &load_controller_key(&files.doc_file)?).await?;
+
+    deactivate_did(&client, stored.id()).await?;
+    let deactivated = client.resolve_did(stored.id()).await.context("cannot resolve the deactivated DID document")?;
+    save_did_document(&files.doc_file, &deactivated)?;
+    Ok(json!({
+        "did": deactivated.id().to_string(),
+        "deactivated": deactivated.metadata.deactivated.unwrap_or(false),
+    }))
+}
+
+// --- Credentials and Presentations ---
+
+async fn vc_issue(
+    issuer: &str,
+    subject_file: &str,
+    credential_type: &str,
+    id: Option<&str>,
+    validity_days: Option<i64>,
+) -> Result<Value> {
+    // 1. Read the subject and load the issuer
+    let subject_json: Value = serde_json::from_str(&std::fs::read_to_string(subject_file).with_context(|| format!("cannot read {}", subject_file))?)
+        .with_context(|| format!("{} is not valid JSON", subject_file))?;
+    let files = IdentityFiles::named(issuer)?;
+    let issuer_doc = load_existing_did(&files.doc_file, issuer)?;
+    let issuer_fragment = std::fs::read_to_string(&files.fragment_file)?.trim().to_string();
+
+    // 2. Build VC
+    let id = id.map(str::to_owned).unwrap_or_else(|| format!("urn:uuid:{}", nanoid::nanoid!()));
+    let mut builder = CredentialBuilder::default()
+        .id(Url::parse(&id).with_context(|| format!("invalid credential id {}", id))?)
+        .issuer(Url::parse(issuer_doc.id().as_str())?)
+        .type_(credential_type)
+        .subject(Subject::from_json_value(subject_json)?)
+        .issuance_date(Timestamp::now_utc());
+    if let Some(days) = validity_days {
+        if days <= 0 {
+            bail!("--validity-days must be positive");
+        }
+        let expires = Timestamp::now_utc().checked_add(Duration::days(days)).context("--validity-days is too large")?;
+        builder = builder.expiration_date(expires);
+    }
+    let credential: Credential<Object> = build_credential(builder)?;
+
+    // 3. Sign VC
+    let vault = open_vault(&files.stronghold_path).await?;
+    let jwt = sign_credential(&issuer_doc, &vault.storage, &issuer_fragment, &credential).await?;
+    Ok(json!({
+        "jwt": jwt.as_str(),
+        "credentialId": id,
+        "issuer": issuer_doc.id().to_string(),
+    }))
+}
+
+async fn vp_create(holder: &str, credentials: &[String], challenge: &str, expires_in_minutes: i64) -> Result<Value> {
+    let credentials = credentials
+        .iter()
+        .map(|credential| read_input(credential).map(Jwt::new))
+        .collect::<Result<Vec<_>>>()?;
+
+    let files = IdentityFiles::named(holder)?;
+    let holder_doc = load_existing_did(&files.doc_file, holder)?;
+    let holder_fragment = std::fs::read_to_string(&files.fragment_file)?.trim().to_string();
+
+    if expires_in_minutes <= 0 {
+        bail!("--expires-in-minutes must be positive");
+    }
+    let expires = Timestamp::now_utc()
+        .checked_add(Duration::minutes(expires_in_minutes))
+        .context("--expires-in-minutes is too large")?;
+
+    let vault = open_vault(&files.stronghold_path).await?;
+    let jwt = create_presentation(
+        &holder_doc,
+        &vault.storage,
+        &holder_fragment,
+        credentials,
+        challenge,
+        &JwtPresentationOptions::default().expiration_date(expires),
+    )
+    .await?;
+    Ok(json!({
+        "jwt": jwt.as_str(),
+        "holder": holder_doc.id().to_string(),
+        "challenge": challenge,
+    }))
+}
+
+// --- Verification and Inspection ---
+
+// Returns the verdict and whether the JWT verified
+async fn verify(args: &VerifyArgs) -> Result<(Value, bool)> {
+    let jwt = read_input(&args.jwt)?;
+    let kind = inspect_jwt(&jwt).kind;
+    let verifier = Verifier::new();
+
+    if kind == JwtKind::Presentation {
+        let verified = verifier
+            .verify_presentation(&Jwt::new(jwt), args.challenge.as_deref(), args.holder_binding.into())
+            .await;
+        return Ok(match verified {
+            Ok(verified) => (
+                json!({
+                    "valid": true,
+                    "holder": verified.holder.to_string(),
+                    "credentials": verified
+                        .credentials
+                        .iter()
+                        .map(|credential| json!({"issuer": credential.issuer.to_string(), "credential": credential.credential}))
+                        .collect::<Vec<_>>(),
+                }),
+                true,
+            ),
+            Err(e) => (json!({"valid": false, "error": e.to_string()}), false),
+        });
+    }
+    if kind == JwtKind::Credential {
+        return Ok(match verifier.verify_credential(&Jwt::new(jwt)).await {
+            Ok(verified) => (
+                json!({"valid": true, "issuer": verified.issuer.to_string(), "credential": verified.credential}),
+                true,
+            ),
+            Err(e) => (json!({"valid": false, "error": e.to_string()}), false),
+        });
+    }
+    bail!("the JWT is neither a presentation (vp claim) nor a credential (vc claim)")
+}
+
+async fn run(command: Command) -> Result<(Value, bool)> {
+    let output = match command {
+        Command::Did(DidCommand::Create { name }) => did_create(&name).await?,
+        Command::Did(DidCommand::Show { identity, resolve }) => did_show(&identity, resolve).await?,
+        Command::Did(DidCommand::Update(args)) => did_update(&args).await?,
+        Command::Did(DidCommand::Deactivate { name }) => did_deactivate(&name).await?,
+        Command::Vc(VcCommand::Issue {
+            issuer,
+            subject,
+            credential_type,
+            id,
+            validity_days,
+        }) => vc_issue(&issuer, &subject, &credential_type, id.as_deref(), validity_days).await?,
+        Command::Vp(VpCommand::Create {
+            holder,
+            credentials,
+            challenge,
+            expires_in_minutes,
+        }) => vp_create(&holder, &credentials, &challenge, expires_in_minutes).await?,
+        Command::Verify(args) => return verify(&args).await,
+        Command::Jwt(JwtCommand::Decode { jwt }) => {
+            let inspection = inspect_jwt(&read_input(&jwt)?);
+            let well_formed = inspection.well_formed;
+            return Ok((serde_json::to_value(inspection)?, well_formed));
+        }
+    };
+    Ok((output, true))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command).await {
+        Ok((output, success)) => {
+            println!("{}", serde_json::to_string_pretty(&output).expect("JSON values serialize"));
+            match success {
+                true => ExitCode::SUCCESS,
+                false => ExitCode::FAILURE,
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", json!({"error": format!("{:#}", e)}));
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! An in-memory cache of resolved DID documents, shared by the verifiers of all requests so that
+//! frequently seen issuers and holders are not resolved again for every presentation.
+
+use identity_iota::{did::CoreDID, document::CoreDocument};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+struct CachedDocument {
+    document: CoreDocument,
+    stored_at: Instant,
+}
+
+/// Counters and limits of a [`DocumentCache`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub entries: usize,
+    pub max_entries: usize,
+    pub ttl_seconds: u64,
+    pub hits: u64,
+    pub misses: u64,
+    /// Lookups skipped because fresh documents were requested.
+    pub bypassed: u64,
+    /// Share of lookups served from the cache, from 0 to 1.
+    pub hit_rate: f64,
+}
+
+/// Resolved DID documents kept for `ttl`, at most `max_entries` of them. The oldest entry is evicted
+/// to make room for a new one. A zero `ttl` or `max_entries` disables caching.
+pub struct DocumentCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<CoreDID, CachedDocument>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    bypassed: AtomicU64,
+}
+
+impl DocumentCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        DocumentCache {
+            ttl,
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            bypassed: AtomicU64::new(0),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<CoreDID, CachedDocument>> {
+        self.entries.lock().expect("DID document cache poisoned")
+    }
+
+    /// Returns the cached document of `did` unless it has expired.
+    pub fn get(&self, did: &CoreDID) -> Option<CoreDocument> {
+        let mut entries = self.entries();
+        let document = match entries.get(did) {
+            Some(cached) if cached.stored_at.elapsed() < self.ttl => Some(cached.document.clone()),
+            Some(_) => {
+                entries.remove(did);
+                None
+            }
+            None => None,
+        };
+        let counter = if document.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        document
+    }
+
+    /// Counts a lookup that went to the network because fresh documents were requested.
+    pub fn record_bypass(&self) {
+        self.bypassed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Stores a freshly resolved document, replacing any cached copy.
+    pub fn insert(&self, did: CoreDID, document: CoreDocument) {
+        if self.ttl.is_zero() || self.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries();
+        if !entries.contains_key(&did) && entries.len() >= self.max_entries {
+            entries.retain(|_, cached| cached.stored_at.elapsed() < self.ttl);
+        }
+        if !entries.contains_key(&did) && entries.len() >= self.max_entries {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, cached)| cached.stored_at)
+                .map(|(did, _)| did.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            did,
+            CachedDocument {
+                document,
+                stored_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Removes the entry of `did`, or every entry if `did` is `None`. Returns how many were removed.
+    pub fn purge(&self, did: Option<&CoreDID>) -> usize {
+        let mut entries = self.entries();
+        match did {
+            Some(did) => entries.remove(did).map_or(0, |_| 1),
+            None => {
+                let purged = entries.len();
+                entries.clear();
+                purged
+            }
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        CacheStats {
+            entries: self.entries().len(),
+            max_entries: self.max_entries,
+            ttl_seconds: self.ttl.as_secs(),
+            hits,
+            misses,
+            bypassed: self.bypassed.load(Ordering::Relaxed),
+            hit_rate: match hits + misses {
+                0 => 0.0,
+                lookups => hits as f64 / lookups as f64,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_iota::core::Object;
+
+    fn document(did: &str) -> (CoreDID, CoreDocument) {
+        let did = CoreDID::parse(did).unwrap();
+        let document = CoreDocument::builder(Object::new()).id(did.clone()).build().unwrap();
+        (did, document)
+    }
+
+    #[test]
+    fn serves_documents_until_they_expire() {
+        let cache = DocumentCache::new(Duration::from_millis(50), 10);
+        let (did, doc) = document("did:example:alice");
+
+        assert_eq!(cache.get(&did), None);
+        cache.insert(did.clone(), doc.clone());
+        assert_eq!(cache.get(&did), Some(doc));
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(&did), None);
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (0, 1, 2));
+        assert!((stats.hit_rate - 1.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn evicts_the_oldest_entry_when_full() {
+        let cache = DocumentCache::new(Duration::from_secs(60), 2);
+        let (alice, alice_doc) = document("did:example:alice");
+        let (bob, bob_doc) = document("did:example:bob");
+        let (carol, carol_doc) = document("did:example:carol");
+
+        cache.insert(alice.clone(), alice_doc);
+        std::thread::sleep(Duration::from_millis(2));
+        cache.insert(bob.clone(), bob_doc);
+        std::thread::sleep(Duration::from_millis(2));
+        cache.insert(carol.clone(), carol_doc);
+
+        assert_eq!(cache.stats().entries, 2);
+        assert_eq!(cache.get(&alice), None);
+        assert!(cache.get(&bob).is_some());
+        assert!(cache.get(&carol).is_some());
+    }
+
+    #[test]
+    fn replacing_an_entry_does_not_evict_another() {
+        let cache = DocumentCache::new(Duration::from_secs(60), 2);
+        let (alice, alice_doc) = document("did:example:alice");
+        let (bob, bob_doc) = document("did:example:bob");
+
+        cache.insert(alice.clone(), alice_doc.clone());
+        cache.insert(bob.clone(), bob_doc);
+        cache.insert(alice.clone(), alice_doc);
+
+        assert_eq!(cache.stats().entries, 2);
+        assert!(cache.get(&bob).is_some());
+    }
+
+    #[test]
+    fn zero_ttl_or_size_disables_caching() {
+        let (did, doc) = document("did:example:alice");
+        for cache in [
+            DocumentCache::new(Duration::ZERO, 10),
+            DocumentCache::new(Duration::from_secs(60), 0),
+        ] {
+            cache.insert(did.clone(), doc.clone());
+            assert_eq!(cache.get(&did), None);
+            assert_eq!(cache.stats().entries, 0);
+        }
+    }
+
+    #[test]
+    fn purges_one_entry_or_all_of_them() {
+        let cache = DocumentCache::new(Duration::from_secs(60), 10);
+        let (alice, alice_doc) = document("did:example:alice");
+        let (bob, bob_doc) = document("did:example:bob");
+        cache.insert(alice.clone(), alice_doc);
+        cache.insert(bob, bob_doc);
+
+        assert_eq!(cache.purge(Some(&alice)), 1);
+        assert_eq!(cache.purge(Some(&alice)), 0);
+        assert_eq!(cache.purge(None), 1);
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn bypasses_are_not_counted_as_lookups() {
+        let cache = DocumentCache::new(Duration::from_secs(60), 10);
+        cache.record_bypass();
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.bypassed), (0, 0, 1));
+        assert_eq!(stats.hit_rate, 0.0);
+    }
+}
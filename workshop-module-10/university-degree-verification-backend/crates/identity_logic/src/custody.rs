@@ -0,0 +1,239 @@
+//! Chain-of-custody checks for products handed from party to party.
+//!
+//! A chain starts with a `ProductOriginCredential`, whose subject is the first custodian, followed by
+//! `CustodyHandoverCredential`s numbered 1, 2, ... in which each custodian hands the product to the next.
+
+use crate::subjects::{CustodyHandoverSubject, ProductOriginSubject};
+
+/// A verified handover credential together with the DID that issued it.
+#[derive(Debug, Clone)]
+pub struct Handover {
+    pub issuer: String,
+    pub subject: CustodyHandoverSubject,
+}
+
+/// Why a chain of custody does not hold.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ChainError {
+    #[error("handover {sequence} is for product {found}, expected {expected}")]
+    ProductMismatch {
+        sequence: u32,
+        expected: String,
+        found: String,
+    },
+    #[error("handover {0} appears more than once")]
+    DuplicateHandover(u32),
+    #[error("handover {expected} is missing (next is {found})")]
+    Gap { expected: u32, found: u32 },
+    #[error("handover {sequence} is out of order: {reason}")]
+    OutOfOrder { sequence: u32, reason: String },
+    #[error("handover {sequence} was made by {found}, but the product was in the custody of {expected}")]
+    CustodianMismatch {
+        sequence: u32,
+        expected: String,
+        found: String,
+    },
+    #[error("the product is in the custody of {expected}, but was presented by {found}")]
+    HolderMismatch { expected: String, found: String },
+}
+
+/// Checks that `handovers`, in the order presented, form an unbroken chain from the product origin
+/// to `holder`. Returns the custodians from the first to the current one.
+pub fn verify_chain(
+    origin: &ProductOriginSubject,
+    handovers: &[Handover],
+    holder: &str,
+) -> Result<Vec<String>, ChainError> {
+    // 1. Every handover must be about the certified product
+    for handover in handovers {
+        if handover.subject.product_id != origin.product_id {
+            return Err(ChainError::ProductMismatch {
+                sequence: handover.subject.sequence,
+                expected: origin.product_id.clone(),
+                found: handover.subject.product_id.clone(),
+            });
+        }
+    }
+
+    // 2. Sequence numbers must be 1..=n without duplicates or gaps, and presented in that order
+    let mut sequences: Vec<u32> = handovers.iter().map(|handover| handover.subject.sequence).collect();
+    sequences.sort_unstable();
+    if let Some(duplicate) = sequences.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(ChainError::DuplicateHandover(duplicate[0]));
+    }
+    for (expected, &found) in (1u32..).zip(&sequences) {
+        if found != expected {
+            return Err(ChainError::Gap { expected, found });
+        }
+    }
+    for (position, handover) in (1u32..).zip(handovers) {
+        if handover.subject.sequence != position {
+            return Err(ChainError::OutOfOrder {
+                sequence: handover.subject.sequence,
+                reason: format!("presented at position {}", position),
+            });
+        }
+    }
+
+    // 3. Each handover must be made by the current custodian, after the previous one
+    let mut custodians = vec![origin.id.clone()];
+    let mut previous_date = origin.production_date;
+    for handover in handovers {
+        let custodian = custodians.last().expect("chain starts with the origin custodian");
+        let sequence = handover.subject.sequence;
+        if &handover.issuer != custodian {
+            return Err(ChainError::CustodianMismatch {
+                sequence,
+                expected: custodian.clone(),
+                found: handover.issuer.clone(),
+            });
+        }
+        if &handover.subject.previous_custodian != custodian {
+            return Err(ChainError::CustodianMismatch {
+                sequence,
+                expected: custodian.clone(),
+                found: handover.subject.previous_custodian.clone(),
+            });
+        }
+        if handover.subject.handover_date < previous_date {
+            return Err(ChainError::OutOfOrder {
+                sequence,
+                reason: format!("dated {}, before the previous step on {}", handover.subject.handover_date, previous_date),
+            });
+        }
+        previous_date = handover.subject.handover_date;
+        custodians.push(handover.subject.id.clone());
+    }
+
+    // 4. Whoever presents the chain must be its last custodian
+    let current = custodians.last().expect("chain starts with the origin custodian");
+    if current != holder {
+        return Err(ChainError::HolderMismatch {
+            expected: current.clone(),
+            found: holder.to_string(),
+        });
+    }
+    Ok(custodians)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_iota::core::Timestamp;
+
+    const LOGISTICS: &str = "did:example:logistics";
+    const CARRIER: &str = "did:example:carrier";
+    const RETAILER: &str = "did:example:retailer";
+
+    fn hours_after_production(hours: i64) -> Timestamp {
+        Timestamp::from_unix(1_700_000_000 + hours * 3600).unwrap()
+    }
+
+    fn origin() -> ProductOriginSubject {
+        ProductOriginSubject::builder()
+            .id(LOGISTICS)
+            .product_id("PKG-1")
+            .factory_origin("Berlin_Smart_Factory_01")
+            .production_date(hours_after_production(0))
+            .build()
+            .unwrap()
+    }
+
+    fn handover(from: &str, to: &str, sequence: u32) -> Handover {
+        Handover {
+            issuer: from.to_string(),
+            subject: CustodyHandoverSubject::builder()
+                .id(to)
+                .product_id("PKG-1")
+                .previous_custodian(from)
+                .sequence(sequence)
+                .handover_date(hours_after_production(sequence.into()))
+                .build()
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn continuous_chain_lists_every_custodian() {
+        let handovers = [handover(LOGISTICS, CARRIER, 1), handover(CARRIER, RETAILER, 2)];
+        assert_eq!(
+            verify_chain(&origin(), &handovers, RETAILER),
+            Ok(vec![LOGISTICS.to_string(), CARRIER.to_string(), RETAILER.to_string()])
+        );
+    }
+
+    #[test]
+    fn origin_alone_is_held_by_its_subject() {
+        assert_eq!(verify_chain(&origin(), &[], LOGISTICS), Ok(vec![LOGISTICS.to_string()]));
+    }
+
+    #[test]
+    fn missing_handover_is_a_gap() {
+        let handovers = [handover(LOGISTICS, CARRIER, 1), handover(CARRIER, RETAILER, 3)];
+        assert_eq!(
+            verify_chain(&origin(), &handovers, RETAILER),
+            Err(ChainError::Gap { expected: 2, found: 3 })
+        );
+    }
+
+    #[test]
+    fn duplicate_handover_is_rejected() {
+        let handovers = [handover(LOGISTICS, CARRIER, 1), handover(LOGISTICS, CARRIER, 1)];
+        assert_eq!(verify_chain(&origin(), &handovers, CARRIER), Err(ChainError::DuplicateHandover(1)));
+    }
+
+    #[test]
+    fn reordered_handovers_are_out_of_order() {
+        let handovers = [handover(CARRIER, RETAILER, 2), handover(LOGISTICS, CARRIER, 1)];
+        assert!(matches!(
+            verify_chain(&origin(), &handovers, RETAILER),
+            Err(ChainError::OutOfOrder { sequence: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn handover_dated_before_the_previous_step_is_out_of_order() {
+        let mut second = handover(CARRIER, RETAILER, 2);
+        second.subject.handover_date = hours_after_production(0);
+        let handovers = [handover(LOGISTICS, CARRIER, 1), second];
+        assert!(matches!(
+            verify_chain(&origin(), &handovers, RETAILER),
+            Err(ChainError::OutOfOrder { sequence: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn handover_by_another_party_is_rejected() {
+        let handovers = [handover(CARRIER, RETAILER, 1)];
+        assert_eq!(
+            verify_chain(&origin(), &handovers, RETAILER),
+            Err(ChainError::CustodianMismatch {
+                sequence: 1,
+                expected: LOGISTICS.to_string(),
+                found: CARRIER.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn handover_of_another_product_is_rejected() {
+        let mut first = handover(LOGISTICS, CARRIER, 1);
+        first.subject.product_id = "PKG-2".to_string();
+        assert!(matches!(
+            verify_chain(&origin(), &[first], CARRIER),
+            Err(ChainError::ProductMismatch { sequence: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn holder_must_be_the_last_custodian() {
+        let handovers = [handover(LOGISTICS, CARRIER, 1)];
+        assert_eq!(
+            verify_chain(&origin(), &handovers, RETAILER),
+            Err(ChainError::HolderMismatch {
+                expected: CARRIER.to_string(),
+                found: RETAILER.to_string(),
+            })
+        );
+    }
+}
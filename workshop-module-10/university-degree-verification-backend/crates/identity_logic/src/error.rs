@@ -0,0 +1,49 @@
+/// Boxed underlying error from the IOTA SDK, identity or storage crates.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Failure kinds of the `identity_logic` helpers.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to connect to network {endpoint}")]
+    NetworkUnavailable {
+        endpoint: String,
+        #[source]
+        source: BoxError,
+    },
+    #[error("env variable IOTA_IDENTITY_PKG_ID must be set")]
+    MissingPackageId,
+    #[error("invalid package id: {0}")]
+    InvalidPackageId(String),
+    #[error("failed to create an IdentityClient")]
+    ClientCreation(#[source] BoxError),
+    #[error("failed to request funds from the faucet")]
+    FaucetFailed(#[source] BoxError),
+    #[error("failed to open the Stronghold vault")]
+    VaultLocked(#[source] BoxError),
+    #[error("failed to generate a key")]
+    KeyGeneration(#[source] BoxError),
+    #[error("failed to publish the DID document")]
+    PublishFailed(#[source] BoxError),
+    #[error("invalid credential subject: {0}")]
+    InvalidSubject(String),
+    #[error("invalid credential: {0}")]
+    InvalidCredential(String),
+    #[error("invalid presentation: {0}")]
+    InvalidPresentation(String),
+    #[error("failed to sign")]
+    Signing(#[source] BoxError),
+    #[error("invalid identity name {0}: use lowercase letters, digits, '-' and '_'")]
+    InvalidName(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("failed to read or write identity files")]
+    Storage(#[source] BoxError),
+    #[error("invalid DID document")]
+    InvalidDocument(#[source] BoxError),
+    #[error("invalid DID {did}: {reason}")]
+    InvalidDid { did: String, reason: String },
+    #[error("cannot resolve {did}: {reason}")]
+    Resolution { did: String, reason: String },
+}
@@ -0,0 +1,362 @@
+//! Decoding credential and presentation JWTs without verifying them.
+//!
+//! [`inspect_jwt`] never fails: anything that would stop validation before it gets to the signature,
+//! such as a missing `kid` or an algorithm the verifier does not support, is reported as a [`Problem`].
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use identity_iota::did::{CoreDID, DIDUrl};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Signature algorithms the verifier can check.
+pub const SUPPORTED_ALGORITHMS: &[&str] = &["EdDSA"];
+
+/// What a JWT carries, judged by its claims.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JwtKind {
+    Credential,
+    Presentation,
+    Unknown,
+}
+
+/// A structural issue that makes validation fail, located by the field it concerns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Problem {
+    /// e.g. `header.kid` or `claims.vp.verifiableCredential[1]`
+    pub field: String,
+    pub message: String,
+}
+
+/// The decoded header and claims of a JWT, with the credentials of a presentation decoded in turn.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Inspection {
+    pub kind: JwtKind,
+    pub header: Value,
+    pub claims: Value,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub credentials: Vec<Inspection>,
+    pub problems: Vec<Problem>,
+    /// No problems here nor in any nested credential.
+    pub well_formed: bool,
+}
+
+struct Problems(Vec<Problem>);
+
+impl Problems {
+    fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.0.push(Problem {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+fn decode_segment(segment: &str) -> Result<Value, String> {
+    let bytes = URL_SAFE_NO_PAD.decode(segment).map_err(|e| format!("not base64url: {}", e))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("not JSON: {}", e))
+}
+
+// Splits a compact JWS into its header, claims and signature segments
+fn split_jwt(jwt: &str) -> Result<[&str; 3], String> {
+    let segments: Vec<&str> = jwt.trim().split('.').collect();
+    <[&str; 3]>::try_from(segments)
+        .map_err(|segments| format!("expected 3 dot-separated segments, found {}", segments.len()))
+}
+
+// Checks `alg` and that `kid` is a DID URL naming the method that signed the JWT
+fn check_header(header: &Value, problems: &mut Problems) -> Option<DIDUrl> {
+    match header.get("alg").and_then(Value::as_str) {
+        None => problems.add("header.alg", "missing alg"),
+        Some(alg) if !SUPPORTED_ALGORITHMS.contains(&alg) => problems.add(
+            "header.alg",
+            format!("unsupported alg {} (supported: {})", alg, SUPPORTED_ALGORITHMS.join(", ")),
+        ),
+        Some(_) => {}
+    }
+
+    let Some(kid) = header.get("kid").and_then(Value::as_str) else {
+        problems.add("header.kid", "missing kid: the verifier cannot tell which verification method signed the JWT");
+        return None;
+    };
+    match DIDUrl::parse(kid) {
+        Ok(kid_url) if kid_url.fragment().is_none() => {
+            problems.add("header.kid", format!("{} has no fragment naming a verification method", kid));
+            None
+        }
+        Ok(kid_url) => Some(kid_url),
+        Err(e) => {
+            problems.add("header.kid", format!("malformed DID URL {}: {}", kid, e));
+            None
+        }
+    }
+}
+
+fn check_did(claims: &Value, claim: &str, problems: &mut Problems) -> Option<CoreDID> {
+    let field = format!("claims.{}", claim);
+    let value = claims.get(claim).and_then(Value::as_str)?;
+    match CoreDID::parse(value) {
+        Ok(did) => Some(did),
+        Err(e) => {
+            problems.add(&field, format!("malformed DID {}: {}", value, e));
+            None
+        }
+    }
+}
+
+fn check_timestamps(claims: &Value, problems: &mut Problems) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default();
+    for claim in ["exp", "nbf", "iat"] {
+        match claims.get(claim) {
+            None => {}
+            Some(value) if !value.is_i64() => problems.add(&format!("claims.{}", claim), "not a number of seconds"),
+            Some(value) if claim == "exp" && value.as_i64() < Some(now) => problems.add("claims.exp", "expired"),
+            Some(value) if claim == "nbf" && value.as_i64() > Some(now) => problems.add("claims.nbf", "not valid yet"),
+            Some(_) => {}
+        }
+    }
+}
+
+fn inspect(jwt: &str, nested: bool) -> Inspection {
+    let mut problems = Problems(Vec::new());
+    let mut kind = JwtKind::Unknown;
+    let mut credentials = Vec::new();
+
+    // 1. Split the compact JWS and decode its header and claims
+    let segments = match split_jwt(jwt) {
+        Ok(segments) => segments,
+        Err(e) => {
+            problems.add("jwt", e);
+            return Inspection {
+                kind,
+                header: Value::Null,
+                claims: Value::Null,
+                credentials,
+                problems: problems.0,
+                well_formed: false,
+            };
+        }
+    };
+    let header = decode_segment(segments[0]).unwrap_or_else(|e| {
+        problems.add("header", e);
+        Value::Null
+    });
+    let claims = decode_segment(segments[1]).unwrap_or_else(|e| {
+        problems.add("claims", e);
+        Value::Null
+    });
+    if segments[2].is_empty() {
+        problems.add("signature", "missing signature");
+    }
+
+    // 2. The header must name a supported algorithm and the signing method of the issuer
+    let kid = check_header(&header, &mut problems);
+    let issuer = match claims.get("iss") {
+        Some(_) => check_did(&claims, "iss", &mut problems),
+        None if claims.is_null() => None,
+        None => {
+            problems.add("claims.iss", "missing iss");
+            None
+        }
+    };
+    if let (Some(kid), Some(issuer)) = (&kid, &issuer) {
+        if kid.did() != issuer {
+            problems.add("header.kid", format!("{} is not a method of the issuer {}", kid, issuer));
+        }
+    }
+    check_timestamps(&claims, &mut problems);
+
+    // 3. Check the credential or presentation, decoding the credentials a presentation contains
+    if let Some(vc) = claims.get("vc") {
+        kind = JwtKind::Credential;
+        if claims.get("sub").and_then(Value::as_str).is_some_and(|sub| sub.starts_with("did:")) {
+            check_did(&claims, "sub", &mut problems);
+        }
+        if vc.get("credentialSubject").is_none() {
+            problems.add("claims.vc.credentialSubject", "missing credentialSubject");
+        }
+        let has_base_type = match vc.get("type") {
+            Some(Value::String(credential_type)) => credential_type == "VerifiableCredential",
+            Some(Value::Array(types)) => types.iter().any(|credential_type| credential_type == "VerifiableCredential"),
+            _ => false,
+        };
+        if !has_base_type {
+            problems.add("claims.vc.type", "type does not include VerifiableCredential");
+        }
+    } else if let Some(vp) = claims.get("vp") {
+        kind = JwtKind::Presentation;
+        if nested {
+            problems.add("claims.vp", "a presentation cannot contain another presentation");
+        }
+        let credential_jwts = match vp.get("verifiableCredential") {
+            Some(Value::Array(credential_jwts)) => credential_jwts.clone(),
+            Some(credential_jwt) => vec![credential_jwt.clone()],
+            None => Vec::new(),
+        };
+        if credential_jwts.is_empty() {
+            problems.add("claims.vp.verifiableCredential", "the presentation contains no credentials");
+        }
+        for (index, credential) in credential_jwts.iter().enumerate() {
+            match credential.as_str() {
+                Some(credential_jwt) => credentials.push(inspect(credential_jwt, true)),
+                None => problems.add(
+                    &format!("claims.vp.verifiableCredential[{}]", index),
+                    "not a JWT; only JWT credentials can be verified",
+                ),
+            }
+        }
+    } else if !claims.is_null() {
+        problems.add("claims", "neither a vc nor a vp claim; not a credential or presentation JWT");
+    }
+
+    let well_formed = problems.0.is_empty() && credentials.iter().all(|credential| credential.well_formed);
+    Inspection {
+        kind,
+        header,
+        claims,
+        credentials,
+        problems: problems.0,
+        well_formed,
+    }
+}
+
+/// Decodes a credential or presentation JWT without verifying it and reports its structural problems.
+pub fn inspect_jwt(jwt: &str) -> Inspection {
+    inspect(jwt, false)
+}
+
+/// Decodes only the claims of a JWT, without verifying it or checking them; fails if the JWT is not
+/// a compact JWS or its claims are not base64url-encoded JSON.
+pub fn decode_claims(jwt: &str) -> Result<Value, String> {
+    let [_, claims, _] = split_jwt(jwt)?;
+    decode_segment(claims).map_err(|e| format!("claims are {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ISSUER: &str = "did:example:university";
+
+    fn jwt(header: Value, claims: Value) -> String {
+        format!(
+            "{}.{}.c2lnbmF0dXJl",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        )
+    }
+
+    fn credential_jwt() -> String {
+        jwt(
+            json!({ "alg": "EdDSA", "kid": format!("{}#key-1", ISSUER) }),
+            json!({
+                "iss": ISSUER,
+                "sub": "did:example:alice",
+                "vc": {
+                    "type": ["VerifiableCredential", "UniversityDegreeCredential"],
+                    "credentialSubject": { "name": "Alice" }
+                }
+            }),
+        )
+    }
+
+    fn fields(inspection: &Inspection) -> Vec<&str> {
+        inspection.problems.iter().map(|problem| problem.field.as_str()).collect()
+    }
+
+    #[test]
+    fn well_formed_credential_has_no_problems() {
+        let inspection = inspect_jwt(&credential_jwt());
+        assert_eq!(inspection.kind, JwtKind::Credential);
+        assert_eq!(inspection.claims["vc"]["credentialSubject"]["name"], "Alice");
+        assert!(inspection.problems.is_empty());
+        assert!(inspection.well_formed);
+    }
+
+    #[test]
+    fn decodes_claims_like_inspection() {
+        let jwt = credential_jwt();
+        assert_eq!(decode_claims(&jwt).unwrap(), inspect_jwt(&jwt).claims);
+        assert_eq!(decode_claims("a.b").unwrap_err(), "expected 3 dot-separated segments, found 2");
+        assert!(decode_claims("e30.bm90IGpzb24.c2ln").unwrap_err().starts_with("claims are not JSON"));
+    }
+
+    #[test]
+    fn reports_a_jwt_without_three_segments() {
+        let inspection = inspect_jwt("not-a-jwt");
+        assert_eq!(fields(&inspection), ["jwt"]);
+        assert_eq!(inspection.header, Value::Null);
+        assert!(!inspection.well_formed);
+    }
+
+    #[test]
+    fn reports_header_problems() {
+        let inspection = inspect_jwt(&jwt(
+            json!({ "alg": "HS256", "kid": "did:example:someone-else#key-1" }),
+            json!({ "iss": ISSUER, "vc": { "type": "VerifiableCredential", "credentialSubject": {} } }),
+        ));
+        assert_eq!(fields(&inspection), ["header.alg", "header.kid"]);
+
+        let inspection = inspect_jwt(&jwt(
+            json!({ "alg": "EdDSA" }),
+            json!({ "iss": ISSUER, "vc": { "type": "VerifiableCredential", "credentialSubject": {} } }),
+        ));
+        assert_eq!(fields(&inspection), ["header.kid"]);
+    }
+
+    #[test]
+    fn reports_claim_problems() {
+        let inspection = inspect_jwt(&jwt(
+            json!({ "alg": "EdDSA", "kid": format!("{}#key-1", ISSUER) }),
+            json!({ "iss": ISSUER, "exp": 1, "vc": { "type": ["UniversityDegreeCredential"] } }),
+        ));
+        assert_eq!(inspection.kind, JwtKind::Credential);
+        assert_eq!(
+            fields(&inspection),
+            ["claims.exp", "claims.vc.credentialSubject", "claims.vc.type"]
+        );
+        assert!(!inspection.well_formed);
+    }
+
+    #[test]
+    fn decodes_the_credentials_of_a_presentation() {
+        let malformed = jwt(
+            json!({ "alg": "EdDSA" }),
+            json!({ "iss": ISSUER, "vc": { "type": "VerifiableCredential", "credentialSubject": {} } }),
+        );
+        let inspection = inspect_jwt(&jwt(
+            json!({ "alg": "EdDSA", "kid": "did:example:alice#key-1" }),
+            json!({
+                "iss": "did:example:alice",
+                "vp": { "verifiableCredential": [credential_jwt(), 42, malformed] }
+            }),
+        ));
+        assert_eq!(inspection.kind, JwtKind::Presentation);
+        assert_eq!(fields(&inspection), ["claims.vp.verifiableCredential[1]"]);
+        assert_eq!(inspection.credentials.len(), 2);
+        assert!(inspection.credentials[0].well_formed);
+        assert_eq!(fields(&inspection.credentials[1]), ["header.kid"]);
+        assert!(!inspection.well_formed);
+    }
+
+    #[test]
+    fn rejects_a_presentation_inside_a_presentation() {
+        let inner = jwt(
+            json!({ "alg": "EdDSA", "kid": "did:example:alice#key-1" }),
+            json!({ "iss": "did:example:alice", "vp": { "verifiableCredential": [credential_jwt()] } }),
+        );
+        let inspection = inspect_jwt(&jwt(
+            json!({ "alg": "EdDSA", "kid": "did:example:alice#key-1" }),
+            json!({ "iss": "did:example:alice", "vp": { "verifiableCredential": inner } }),
+        ));
+        assert!(inspection.problems.is_empty());
+        assert_eq!(fields(&inspection.credentials[0]), ["claims.vp"]);
+        assert!(!inspection.well_formed);
+    }
+}
@@ -0,0 +1,48 @@
+//! Building and signing credentials.
+
+use identity_iota::{
+    core::{Object, Url},
+    credential::{Credential, CredentialBuilder, Jwt},
+    iota::IotaDocument,
+};
+use identity_storage::{JwkDocumentExt, JwkStorage, JwsSignatureOptions, KeyIdStorage, Storage};
+
+use crate::{subjects::TypedSubject, Error, Result};
+
+/// Starts credential `id` of the subject's type, issued by `issuer_doc`.
+/// Add a schema, status or validity period before building it.
+pub fn credential_builder<T: TypedSubject>(
+    id: &str,
+    issuer_doc: &IotaDocument,
+    subject: &T,
+) -> Result<CredentialBuilder<Object>> {
+    let id = Url::parse(id).map_err(|e| Error::InvalidCredential(format!("invalid credential id {}: {}", id, e)))?;
+    let issuer = Url::parse(issuer_doc.id().as_str()).map_err(|e| Error::InvalidCredential(e.to_string()))?;
+    Ok(CredentialBuilder::default()
+        .id(id)
+        .issuer(issuer)
+        .type_(T::CREDENTIAL_TYPE)
+        .subject(subject.to_subject()?))
+}
+
+/// Builds the credential, reporting missing or inconsistent fields as [`Error::InvalidCredential`].
+pub fn build_credential(builder: CredentialBuilder<Object>) -> Result<Credential<Object>> {
+    builder.build().map_err(|e| Error::InvalidCredential(e.to_string()))
+}
+
+/// Signs `credential` as a JWT with the issuer's verification method `fragment`.
+pub async fn sign_credential<K, I>(
+    issuer_doc: &IotaDocument,
+    storage: &Storage<K, I>,
+    fragment: &str,
+    credential: &Credential<Object>,
+) -> Result<Jwt>
+where
+    K: JwkStorage,
+    I: KeyIdStorage,
+{
+    issuer_doc
+        .create_credential_jwt(credential, storage, fragment, &JwsSignatureOptions::default(), None)
+        .await
+        .map_err(|e| Error::Signing(e.into()))
+}
@@ -1,6 +1,18 @@
+mod error;
+pub mod cache;
+pub mod custody;
+pub mod inspect;
+pub mod issuance;
+pub mod presentation;
+pub mod resolution;
+pub mod store;
+pub mod subjects;
+pub mod verification;
+
+pub use error::{BoxError, Error, Result};
+
 use std::path::PathBuf;
-use anyhow::Context;
-use identity_iota::iota::IotaDocument;
+use identity_iota::iota::{IotaDID, IotaDocument};
 use identity_iota::iota_interaction::OptionalSync;
 use identity_iota::storage::{JwkMemStore, KeyIdMemstore, Storage};
 use identity_iota::verification::jws::JwsAlgorithm;
@@ -8,7 +20,8 @@ use identity_iota::verification::MethodScope;
 
 use identity_iota::iota::rebased::client::{IdentityClient, IdentityClientReadOnly, IotaKeySignature};
 use identity_iota::iota::rebased::utils::request_funds;
-use identity_storage::{JwkStorage, KeyIdStorage, KeyType, StorageSigner};
+use identity_iota::verification::jwk::Jwk;
+use identity_storage::{JwkStorage, KeyId, KeyIdStorage, KeyType, StorageSigner};
 use identity_stronghold::StrongholdStorage;
 
 use iota_sdk::types::base_types::IotaAddress;
@@ -19,21 +32,46 @@ use iota_sdk_legacy::client::Password;
 
 use rand::distributions::DistString;
 use secret_storage::Signer;
+use serde::{Deserialize, Serialize};
 use identity_storage::JwkDocumentExt;
 
 pub const TEST_GAS_BUDGET: u64 = 50_000_000;
 pub type MemStorage = Storage<JwkMemStore, KeyIdMemstore>;
 
+/// Stages reported while a DID document is being published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishStage {
+    Funding,
+    Signing,
+    Submitted,
+    Confirmed,
+}
+
 /// Creates a new IotaDocument, generates a key, publishes the DID to the network, and returns the document and fragment.
 pub async fn create_did_document<K, I, S>(
     identity_client: &IdentityClient<S>,
     storage: &Storage<K, I>,
-) -> anyhow::Result<(IotaDocument, String)>
+) -> Result<(IotaDocument, String)>
+where
+    K: identity_storage::JwkStorage,
+    I: identity_storage::KeyIdStorage,
+    S: Signer<IotaKeySignature> + OptionalSync,
+{
+    create_did_document_with_progress(identity_client, storage, &|_| {}).await
+}
+
+/// Same as [`create_did_document`], calling `progress` as the publication moves through its stages.
+pub async fn create_did_document_with_progress<K, I, S>(
+    identity_client: &IdentityClient<S>,
+    storage: &Storage<K, I>,
+    progress: &(dyn Fn(PublishStage) + Send + Sync),
+) -> Result<(IotaDocument, String)>
 where
     K: identity_storage::JwkStorage,
     I: identity_storage::KeyIdStorage,
     S: Signer<IotaKeySignature> + OptionalSync,
 {
+    progress(PublishStage::Signing);
     let mut unpublished: IotaDocument = IotaDocument::new(identity_client.network());
 
     let verification_method_fragment = unpublished
@@ -44,14 +82,18 @@ where
             None,
             MethodScope::VerificationMethod,
         )
-        .await?;
+        .await
+        .map_err(|e| Error::KeyGeneration(e.into()))?;
 
+    progress(PublishStage::Submitted);
     let document = identity_client
         .publish_did_document(unpublished)
         .with_gas_budget(TEST_GAS_BUDGET)
         .build_and_execute(identity_client)
-        .await?
+        .await
+        .map_err(|e| Error::PublishFailed(e.into()))?
         .output;
+    progress(PublishStage::Confirmed);
 
     Ok((document, verification_method_fragment))
 }
@@ -68,12 +110,13 @@ pub fn random_stronghold_path() -> PathBuf {
 /// Returns a StrongholdStorage instance for persistent key storage.
 pub fn get_stronghold_storage(
     path: Option<PathBuf>,
-) -> Result<Storage<StrongholdStorage, StrongholdStorage>, anyhow::Error> {
+) -> Result<Storage<StrongholdStorage, StrongholdStorage>> {
     let path = path.unwrap_or_else(random_stronghold_path);
     let password = Password::from("secure_password".to_owned());
     let stronghold = StrongholdSecretManager::builder()
         .password(password.clone())
-        .build(path.clone())?;
+        .build(path.clone())
+        .map_err(|e| Error::VaultLocked(e.into()))?;
     let stronghold_storage = StrongholdStorage::new(stronghold);
     Ok(Storage::new(
         stronghold_storage.clone(),
@@ -82,33 +125,52 @@ pub fn get_stronghold_storage(
 }
 
 /// Returns a read-only IOTA Identity client.
-pub async fn get_read_only_client() -> anyhow::Result<IdentityClientReadOnly> {
+pub async fn get_read_only_client() -> Result<IdentityClientReadOnly> {
     let api_endpoint =
         std::env::var("API_ENDPOINT").unwrap_or_else(|_| IOTA_LOCAL_NETWORK_URL.to_string());
 
     let iota_client = IotaClientBuilder::default()
         .build(&api_endpoint)
         .await
-        .map_err(|err| anyhow::anyhow!(format!("failed to connect to network; {}", err)))?;
+        .map_err(|e| Error::NetworkUnavailable {
+            endpoint: api_endpoint.clone(),
+            source: e.into(),
+        })?;
 
-    let package_id = std::env::var("IOTA_IDENTITY_PKG_ID")
-        .map_err(|e| {
-            anyhow::anyhow!(
-                "env variable IOTA_IDENTITY_PKG_ID must be set in order to run the examples"
-            )
-            .context(e)
-        })
-        .and_then(|pkg_str| pkg_str.parse().context("invalid package id"))?;
+    let pkg_str = std::env::var("IOTA_IDENTITY_PKG_ID").map_err(|_| Error::MissingPackageId)?;
+    let package_id = pkg_str
+        .parse()
+        .map_err(|_| Error::InvalidPackageId(pkg_str.clone()))?;
 
     IdentityClientReadOnly::new_with_pkg_id(iota_client, package_id)
         .await
-        .context("failed to create a read-only IdentityClient")
+        .map_err(|e| Error::ClientCreation(e.into()))
+}
+
+/// The key whose address controls the identities an [`IdentityClient`] publishes.
+/// Keep it to update those identities later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControllerKey {
+    pub key_id: KeyId,
+    pub public_key_jwk: Jwk,
 }
 
 /// Returns an IOTA Identity client funded for publishing operations.
 pub async fn get_funded_client<K, I>(
     storage: &Storage<K, I>,
-) -> Result<IdentityClient<StorageSigner<K, I>>, anyhow::Error>
+) -> Result<IdentityClient<StorageSigner<K, I>>>
+where
+    K: JwkStorage,
+    I: KeyIdStorage,
+{
+    get_funded_controller(storage).await.map(|(client, _)| client)
+}
+
+/// Same as [`get_funded_client`], also returning the newly generated controller key.
+pub async fn get_funded_controller<K, I>(
+    storage: &Storage<K, I>,
+) -> Result<(IdentityClient<StorageSigner<K, I>>, ControllerKey)>
 where
     K: JwkStorage,
     I: KeyIdStorage,
@@ -116,20 +178,69 @@ where
     let generate = storage
         .key_storage()
         .generate(KeyType::new("Ed25519"), JwsAlgorithm::EdDSA)
-        .await?;
-
-    let public_key_jwk = generate
-        .jwk
-        .to_public()
-        .expect("public components should be derivable");
+        .await
+        .map_err(|e| Error::KeyGeneration(e.into()))?;
+
+    let controller = ControllerKey {
+        key_id: generate.key_id,
+        public_key_jwk: generate
+            .jwk
+            .to_public()
+            .expect("public components should be derivable"),
+    };
+
+    let identity_client = get_controller_client(storage, &controller).await?;
+    Ok((identity_client, controller))
+}
 
-    let signer = StorageSigner::new(storage, generate.key_id, public_key_jwk);
-    let sender_address = IotaAddress::from(&Signer::public_key(&signer).await?);
+/// Returns a funded IOTA Identity client signing with an existing controller key, e.g. to update a DID.
+pub async fn get_controller_client<K, I>(
+    storage: &Storage<K, I>,
+    controller: &ControllerKey,
+) -> Result<IdentityClient<StorageSigner<K, I>>>
+where
+    K: JwkStorage,
+    I: KeyIdStorage,
+{
+    let signer = StorageSigner::new(storage, controller.key_id.clone(), controller.public_key_jwk.clone());
+    let public_key = Signer::public_key(&signer)
+        .await
+        .map_err(|e| Error::KeyGeneration(e.into()))?;
+    let sender_address = IotaAddress::from(&public_key);
 
-    request_funds(&sender_address).await?;
+    request_funds(&sender_address)
+        .await
+        .map_err(|e| Error::FaucetFailed(e.into()))?;
 
     let read_only_client = get_read_only_client().await?;
-    let identity_client = IdentityClient::new(read_only_client, signer).await?;
+    let identity_client = IdentityClient::new(read_only_client, signer)
+        .await
+        .map_err(|e| Error::ClientCreation(e.into()))?;
 
     Ok(identity_client)
 }
+
+/// Publishes a new version of a DID document the client controls and returns the published document.
+pub async fn publish_did_update<S>(
+    identity_client: &IdentityClient<S>,
+    document: IotaDocument,
+) -> Result<IotaDocument>
+where
+    S: Signer<IotaKeySignature> + OptionalSync,
+{
+    identity_client
+        .publish_did_document_update(document, TEST_GAS_BUDGET)
+        .await
+        .map_err(|e| Error::PublishFailed(e.into()))
+}
+
+/// Deactivates a DID the client controls. Resolving it afterwards yields a document marked as deactivated.
+pub async fn deactivate_did<S>(identity_client: &IdentityClient<S>, did: &IotaDID) -> Result<()>
+where
+    S: Signer<IotaKeySignature> + OptionalSync,
+{
+    identity_client
+        .deactivate_did_output(did, TEST_GAS_BUDGET)
+        .await
+        .map_err(|e| Error::PublishFailed(e.into()))
+}
@@ -0,0 +1,47 @@
+//! Building and signing presentations.
+
+use identity_iota::{
+    credential::{Jwt, JwtPresentationOptions, Presentation, PresentationBuilder},
+    iota::IotaDocument,
+};
+use identity_storage::{JwkDocumentExt, JwkStorage, JwsSignatureOptions, KeyIdStorage, Storage};
+
+use crate::{Error, Result};
+
+/// Wraps `credentials` in a presentation by `holder_doc`, signed with its verification method `fragment`.
+/// The `challenge` is signed into the JWS header as its nonce.
+pub async fn create_presentation<K, I>(
+    holder_doc: &IotaDocument,
+    storage: &Storage<K, I>,
+    fragment: &str,
+    credentials: Vec<Jwt>,
+    challenge: &str,
+    options: &JwtPresentationOptions,
+) -> Result<Jwt>
+where
+    K: JwkStorage,
+    I: KeyIdStorage,
+{
+    if credentials.is_empty() {
+        return Err(Error::InvalidPresentation("no credentials selected for the presentation".to_string()));
+    }
+
+    let mut presentation_builder = PresentationBuilder::new(holder_doc.id().to_url().into(), Default::default());
+    for credential in credentials {
+        presentation_builder = presentation_builder.credential(credential);
+    }
+    let presentation: Presentation<Jwt> = presentation_builder
+        .build()
+        .map_err(|e| Error::InvalidPresentation(format!("cannot build presentation: {}", e)))?;
+
+    holder_doc
+        .create_presentation_jwt(
+            &presentation,
+            storage,
+            fragment,
+            &JwsSignatureOptions::default().nonce(challenge.to_owned()),
+            options,
+        )
+        .await
+        .map_err(|e| Error::Signing(e.into()))
+}
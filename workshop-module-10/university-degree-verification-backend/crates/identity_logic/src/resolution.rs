@@ -0,0 +1,386 @@
+//! DID methods resolved without the IOTA network: `did:key` and `did:jwk` are expanded locally,
+//! `did:web` documents are fetched over HTTPS. All of them produce a [`CoreDocument`] with the keys
+//! the DID encodes or publishes, so credentials and presentations they sign validate like any other.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use identity_iota::{
+    core::{BaseEncoding, FromJson, Object, Url},
+    did::{CoreDID, DID},
+    document::CoreDocument,
+    verification::{
+        jwk::{Jwk, JwkParamsOkp},
+        MethodRelationship, MethodScope, VerificationMethod,
+    },
+};
+
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+// Multicodec prefix of an Ed25519 public key
+const ED25519_PUB_CODEC: [u8; 2] = [0xed, 0x01];
+
+/// How long a `did:web` host may take to serve a document.
+pub const DID_WEB_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Set to `true` to fetch `did:web` documents on `localhost` over plain HTTP, e.g. while testing.
+pub const DID_WEB_ALLOW_HTTP_ENV: &str = "DID_WEB_ALLOW_HTTP_LOCALHOST";
+
+fn unresolvable(did: &CoreDID, reason: impl Into<String>) -> Error {
+    Error::Resolution {
+        did: did.to_string(),
+        reason: reason.into(),
+    }
+}
+
+fn invalid_did(did: &CoreDID, reason: impl Into<String>) -> Error {
+    Error::InvalidDid {
+        did: did.to_string(),
+        reason: reason.into(),
+    }
+}
+
+// A document whose only key is `jwk`, usable for authentication and assertions
+fn single_key_document(did: &CoreDID, fragment: &str, jwk: Jwk) -> Result<CoreDocument> {
+    let method = VerificationMethod::new_from_jwk(did.clone(), jwk, Some(fragment))
+        .map_err(|e| invalid_did(did, format!("invalid key: {}", e)))?;
+    let method_id = method.id().clone();
+
+    let mut document = CoreDocument::builder(Object::new())
+        .id(did.clone())
+        .build()
+        .map_err(|e| Error::InvalidDocument(e.into()))?;
+    document
+        .insert_method(method, MethodScope::VerificationMethod)
+        .map_err(|e| Error::InvalidDocument(e.into()))?;
+    for relationship in [
+        MethodRelationship::Authentication,
+        MethodRelationship::AssertionMethod,
+        MethodRelationship::CapabilityInvocation,
+        MethodRelationship::CapabilityDelegation,
+    ] {
+        document
+            .attach_method_relationship(&method_id, relationship)
+            .map_err(|e| Error::InvalidDocument(e.into()))?;
+    }
+    Ok(document)
+}
+
+/// Expands a `did:jwk`, whose method-specific ID is the base64url-encoded public JWK, into its document.
+pub fn expand_did_jwk(did: &CoreDID) -> Result<CoreDocument> {
+    let json = URL_SAFE_NO_PAD
+        .decode(did.method_id())
+        .map_err(|e| invalid_did(did, format!("method-specific ID is not base64url: {}", e)))?;
+    let jwk: Jwk = serde_json::from_slice(&json).map_err(|e| invalid_did(did, format!("not a JWK: {}", e)))?;
+    if !jwk.is_public() {
+        return Err(invalid_did(did, "the JWK contains private key material"));
+    }
+    single_key_document(did, "0", jwk)
+}
+
+/// Expands a `did:key` into its document. Only Ed25519 keys are supported, as the only ones EdDSA can verify.
+pub fn expand_did_key(did: &CoreDID) -> Result<CoreDocument> {
+    let multibase = did.method_id();
+    if !multibase.starts_with('z') {
+        return Err(invalid_did(did, "method-specific ID is not base58btc multibase"));
+    }
+    let bytes = BaseEncoding::decode_multibase(multibase)
+        .map_err(|e| invalid_did(did, format!("invalid multibase: {}", e)))?;
+    let Some(public_key) = bytes.strip_prefix(&ED25519_PUB_CODEC).filter(|key| key.len() == 32) else {
+        return Err(invalid_did(did, "only Ed25519 keys are supported"));
+    };
+
+    let mut params = JwkParamsOkp::new();
+    params.crv = "Ed25519".to_string();
+    params.x = URL_SAFE_NO_PAD.encode(public_key);
+    let mut jwk = Jwk::from_params(params);
+    jwk.set_alg("EdDSA");
+    single_key_document(did, multibase, jwk)
+}
+
+/// Fetches `did:web` documents from the domain they name.
+#[derive(Debug, Clone)]
+pub struct DidWebResolver {
+    client: reqwest::Client,
+    // Fetch documents on localhost over plain HTTP instead of HTTPS
+    allow_http_localhost: bool,
+}
+
+impl Default for DidWebResolver {
+    fn default() -> Self {
+        Self::new(DID_WEB_TIMEOUT, false)
+    }
+}
+
+impl DidWebResolver {
+    /// A resolver giving up on a host after `timeout`. With `allow_http_localhost`, documents on
+    /// `localhost` are fetched over plain HTTP, so a local server can stand in for a domain.
+    /// Redirects are not followed: they would let a host point the server at internal addresses, or
+    /// at plain HTTP.
+    pub fn new(timeout: Duration, allow_http_localhost: bool) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            // Only fails if the TLS backend cannot be initialized, in which `Client::new` panics as well
+            .expect("failed to initialize the HTTP client");
+        DidWebResolver {
+            client,
+            allow_http_localhost,
+        }
+    }
+
+    /// A resolver with the default timeout that allows plain HTTP on `localhost` if
+    /// `DID_WEB_ALLOW_HTTP_LOCALHOST` is `true`.
+    pub fn from_env() -> Self {
+        let allow_http_localhost = std::env::var(DID_WEB_ALLOW_HTTP_ENV).is_ok_and(|value| value == "true");
+        Self::new(DID_WEB_TIMEOUT, allow_http_localhost)
+    }
+
+    /// Where the document of `did` is published: `did:web:example.com` is served at
+    /// `https://example.com/.well-known/did.json`, `did:web:example.com:users:alice` at
+    /// `https://example.com/users/alice/did.json`. If allowed, documents on `localhost` are fetched over
+    /// plain HTTP, e.g. `did:web:localhost%3A8080` from `http://localhost:8080/.well-known/did.json`.
+    pub fn document_url(&self, did: &CoreDID) -> Result<Url> {
+        let mut segments = did.method_id().split(':');
+        let host = segments.next().unwrap_or_default().replace("%3A", ":").replace("%3a", ":");
+        let path: Vec<&str> = segments.collect();
+        if host.is_empty() || path.iter().any(|segment| segment.is_empty()) {
+            return Err(invalid_did(did, "malformed did:web identifier"));
+        }
+
+        let hostname = host.split(':').next().unwrap_or_default();
+        let scheme = match hostname {
+            "localhost" | "127.0.0.1" if self.allow_http_localhost => "http",
+            _ => "https",
+        };
+        let path = match path.is_empty() {
+            true => ".well-known".to_string(),
+            false => path.join("/"),
+        };
+        Url::parse(&format!("{}://{}/{}/did.json", scheme, host, path))
+            .map_err(|e| invalid_did(did, format!("invalid document URL: {}", e)))
+    }
+
+    /// Fetches the document and checks it is the document of `did`. A domain serving no document
+    /// yields [`Error::NotFound`].
+    pub async fn resolve(&self, did: &CoreDID) -> Result<CoreDocument> {
+        let url = self.document_url(did)?;
+        let response = self
+            .client
+            .get(url.as_str())
+            .send()
+            .await
+            .map_err(|e| unresolvable(did, format!("cannot fetch {}: {}", url, e)))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(Error::NotFound(format!("no DID document for {} at {}", did, url)));
+        }
+        if response.status().is_redirection() {
+            return Err(unresolvable(did, format!("{} redirects elsewhere; the document must be served at that URL", url)));
+        }
+        let response = response
+            .error_for_status()
+            .map_err(|e| unresolvable(did, format!("cannot fetch {}: {}", url, e)))?;
+        let json = response
+            .text()
+            .await
+            .map_err(|e| unresolvable(did, format!("cannot read {}: {}", url, e)))?;
+
+        let document = CoreDocument::from_json(&json).map_err(|e| unresolvable(did, format!("{} is not a DID document: {}", url, e)))?;
+        if document.id() != did {
+            return Err(unresolvable(did, format!("{} holds the document of {}", url, document.id())));
+        }
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use identity_iota::verification::jwk::JwkParams;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    // Example from the did:key specification, with the key it encodes
+    const DID_KEY: &str = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+    const DID_KEY_X: &str = "Lm_M42cB3HkUiODQsXRcweM6TByfzEHGO9ND274JcOY";
+
+    fn only_key(document: &CoreDocument) -> (String, JwkParamsOkp) {
+        let methods = document.methods(None);
+        assert_eq!(methods.len(), 1);
+        let method = methods[0];
+        let jwk = method.data().public_key_jwk().expect("the method holds a JWK");
+        let JwkParams::Okp(params) = jwk.params() else {
+            panic!("not an OKP key");
+        };
+        (method.id().fragment().unwrap_or_default().to_string(), params.clone())
+    }
+
+    #[test]
+    fn expands_an_ed25519_did_key() {
+        let did = CoreDID::parse(DID_KEY).unwrap();
+        let document = expand_did_key(&did).unwrap();
+
+        assert_eq!(document.id(), &did);
+        let (fragment, params) = only_key(&document);
+        assert_eq!(fragment, did.method_id());
+        assert_eq!(params.crv, "Ed25519");
+        assert_eq!(params.x, DID_KEY_X);
+        assert_eq!(document.authentication().len(), 1);
+        assert_eq!(document.assertion_method().len(), 1);
+    }
+
+    #[test]
+    fn rejects_other_did_keys() {
+        for did in [
+            // secp256k1
+            "did:key:zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme",
+            // not base58btc
+            "did:key:mO3Vz",
+        ] {
+            let did = CoreDID::parse(did).unwrap();
+            assert!(matches!(expand_did_key(&did), Err(Error::InvalidDid { .. })), "{} was expanded", did);
+        }
+    }
+
+    #[test]
+    fn expands_a_did_jwk() {
+        let jwk = serde_json::json!({ "kty": "OKP", "crv": "Ed25519", "x": DID_KEY_X });
+        let did = CoreDID::parse(format!("did:jwk:{}", URL_SAFE_NO_PAD.encode(jwk.to_string()))).unwrap();
+        let document = expand_did_jwk(&did).unwrap();
+
+        assert_eq!(document.id(), &did);
+        let (fragment, params) = only_key(&document);
+        assert_eq!(fragment, "0");
+        assert_eq!(params.x, DID_KEY_X);
+    }
+
+    #[test]
+    fn rejects_a_did_jwk_with_a_private_key() {
+        let jwk = serde_json::json!({ "kty": "OKP", "crv": "Ed25519", "x": DID_KEY_X, "d": DID_KEY_X });
+        let did = CoreDID::parse(format!("did:jwk:{}", URL_SAFE_NO_PAD.encode(jwk.to_string()))).unwrap();
+        assert!(matches!(expand_did_jwk(&did), Err(Error::InvalidDid { .. })));
+
+        let did = CoreDID::parse("did:jwk:not-json").unwrap();
+        assert!(matches!(expand_did_jwk(&did), Err(Error::InvalidDid { .. })));
+    }
+
+    #[test]
+    fn did_web_urls_use_https_unless_localhost_is_allowed() {
+        let secure = DidWebResolver::default();
+        let local = DidWebResolver::new(DID_WEB_TIMEOUT, true);
+        let url = |resolver: &DidWebResolver, did: &str| resolver.document_url(&CoreDID::parse(did).unwrap()).unwrap().to_string();
+
+        assert_eq!(url(&secure, "did:web:example.com"), "https://example.com/.well-known/did.json");
+        assert_eq!(url(&secure, "did:web:example.com:users:alice"), "https://example.com/users/alice/did.json");
+        assert_eq!(url(&secure, "did:web:localhost%3A8080"), "https://localhost:8080/.well-known/did.json");
+        assert_eq!(url(&local, "did:web:localhost%3A8080"), "http://localhost:8080/.well-known/did.json");
+        assert_eq!(url(&local, "did:web:example.com"), "https://example.com/.well-known/did.json");
+    }
+
+    // A listener on a free local port, and the did:web naming it
+    pub(crate) async fn local_host() -> (TcpListener, CoreDID) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        (listener, CoreDID::parse(format!("did:web:127.0.0.1%3A{}", port)).unwrap())
+    }
+
+    // Answers one request: `document` at /.well-known/did.json, 404 anywhere else
+    pub(crate) fn serve_once(listener: TcpListener, document: Option<String>) {
+        serve(listener, vec![document]);
+    }
+
+    // Answers one request per entry of `documents`, in turn, like `serve_once`
+    pub(crate) fn serve(listener: TcpListener, documents: Vec<Option<String>>) {
+        tokio::spawn(async move {
+            for document in documents {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = vec![0u8; 4096];
+                let read = stream.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..read]);
+                let response = match document {
+                    Some(body) if request.starts_with("GET /.well-known/did.json ") => format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    ),
+                    _ => "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n".to_string(),
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+                stream.shutdown().await.unwrap();
+            }
+        });
+    }
+
+    fn local_resolver(timeout: Duration) -> DidWebResolver {
+        DidWebResolver::new(timeout, true)
+    }
+
+    #[tokio::test]
+    async fn resolves_a_did_web_document_from_a_local_server() {
+        let (listener, did) = local_host().await;
+        let document = CoreDocument::builder(Object::new()).id(did.clone()).build().unwrap();
+        serve_once(listener, Some(serde_json::to_string(&document).unwrap()));
+
+        assert_eq!(local_resolver(DID_WEB_TIMEOUT).resolve(&did).await.unwrap(), document);
+    }
+
+    #[tokio::test]
+    async fn a_missing_did_web_document_is_not_found() {
+        let (listener, did) = local_host().await;
+        serve_once(listener, None);
+
+        assert!(matches!(local_resolver(DID_WEB_TIMEOUT).resolve(&did).await, Err(Error::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn rejects_the_document_of_another_did() {
+        let (listener, did) = local_host().await;
+        let other = CoreDocument::builder(Object::new())
+            .id(CoreDID::parse("did:web:example.com").unwrap())
+            .build()
+            .unwrap();
+        serve_once(listener, Some(serde_json::to_string(&other).unwrap()));
+
+        assert!(matches!(local_resolver(DID_WEB_TIMEOUT).resolve(&did).await, Err(Error::Resolution { .. })));
+    }
+
+    #[tokio::test]
+    async fn does_not_follow_redirects() {
+        // The document of the DID is served, but only behind a redirect from the URL the DID names
+        let (listener, did) = local_host().await;
+        let (target, _) = local_host().await;
+        let target_url = format!("http://{}/.well-known/did.json", target.local_addr().unwrap());
+        let document = CoreDocument::builder(Object::new()).id(did.clone()).build().unwrap();
+        serve_once(target, Some(serde_json::to_string(&document).unwrap()));
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 4096];
+            let read = stream.read(&mut request).await.unwrap();
+            assert!(read > 0);
+            let response = format!(
+                "HTTP/1.1 302 Found\r\nlocation: {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                target_url
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        });
+
+        assert!(matches!(local_resolver(DID_WEB_TIMEOUT).resolve(&did).await, Err(Error::Resolution { .. })));
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_a_host_that_does_not_answer() {
+        let (listener, did) = local_host().await;
+        tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let resolved = local_resolver(Duration::from_millis(100)).resolve(&did).await;
+        assert!(matches!(resolved, Err(Error::Resolution { .. })));
+    }
+}
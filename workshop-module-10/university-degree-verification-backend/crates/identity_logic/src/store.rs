@@ -0,0 +1,162 @@
+//! DIDs persisted on disk: `<name>_doc.json`, `<name>_fragment.txt`, `<name>_controller.json` and the
+//! `<name>.stronghold` vault holding their keys.
+
+use identity_iota::{
+    core::{FromJson, ToJson},
+    iota::IotaDocument,
+};
+use identity_storage::Storage;
+use identity_stronghold::StrongholdStorage;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use crate::{create_did_document_with_progress, get_funded_controller, get_stronghold_storage, ControllerKey, Error, PublishStage, Result};
+
+/// One async mutex per Stronghold file, so each identity is created and used by one request at a time.
+static IDENTITY_LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<AsyncMutex<()>>>>> = OnceLock::new();
+
+async fn lock_identity(stronghold_path: &str) -> OwnedMutexGuard<()> {
+    let lock = {
+        let mut locks = IDENTITY_LOCKS
+            .get_or_init(Default::default)
+            .lock()
+            .expect("identity lock map poisoned");
+        locks.entry(PathBuf::from(stronghold_path)).or_default().clone()
+    };
+    lock.lock_owned().await
+}
+
+/// The files backing one identity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityFiles {
+    pub doc_file: String,
+    pub fragment_file: String,
+    pub stronghold_path: String,
+}
+
+impl IdentityFiles {
+    /// Files of the identity `name` in the working directory, e.g. `./issuer_doc.json` for `issuer`.
+    /// Names are restricted to lowercase letters, digits, `-` and `_` so they cannot escape the directory.
+    pub fn named(name: &str) -> Result<Self> {
+        let valid = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid {
+            return Err(Error::InvalidName(name.to_string()));
+        }
+        Ok(IdentityFiles {
+            doc_file: format!("./{}_doc.json", name),
+            fragment_file: format!("./{}_fragment.txt", name),
+            stronghold_path: format!("./{}.stronghold", name),
+        })
+    }
+
+    /// Whether the identity has been created.
+    pub fn exists(&self) -> bool {
+        Path::new(&self.doc_file).exists()
+    }
+}
+
+/// An open Stronghold vault; other requests for the same identity wait until it is dropped.
+pub struct VaultSession {
+    pub storage: Storage<StrongholdStorage, StrongholdStorage>,
+    _guard: OwnedMutexGuard<()>,
+}
+
+/// Waits for exclusive access to the identity's vault and opens it.
+pub async fn open_vault(stronghold_path: &str) -> Result<VaultSession> {
+    let guard = lock_identity(stronghold_path).await;
+    let storage = get_stronghold_storage(Some(PathBuf::from(stronghold_path)))?;
+    Ok(VaultSession { storage, _guard: guard })
+}
+
+// Writes to a temporary file first so readers never observe a partially written file
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp_path = PathBuf::from(format!("{}.tmp", path.display()));
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)
+}
+
+// The controller key of `./issuer_doc.json` is kept in `./issuer_controller.json`
+fn controller_file(doc_file: &str) -> PathBuf {
+    let stem = doc_file.strip_suffix("_doc.json").unwrap_or(doc_file.trim_end_matches(".json"));
+    PathBuf::from(format!("{}_controller.json", stem))
+}
+
+/// Creates or loads a DID document and its signing fragment.
+/// Concurrent callers for the same identity are serialized and all receive the same DID.
+pub async fn create_or_load_did(
+    doc_file: &str,
+    fragment_file: &str,
+    stronghold_path: &str,
+) -> Result<(IotaDocument, String)> {
+    create_or_load_did_with_progress(doc_file, fragment_file, stronghold_path, &|_| {}).await
+}
+
+/// Same as [`create_or_load_did`], reporting publication stages when a new DID has to be created.
+pub async fn create_or_load_did_with_progress(
+    doc_file: &str,
+    fragment_file: &str,
+    stronghold_path: &str,
+    progress: &(dyn Fn(PublishStage) + Send + Sync),
+) -> Result<(IotaDocument, String)> {
+    let vault = open_vault(stronghold_path).await?;
+
+    if !PathBuf::from(doc_file).exists() {
+        progress(PublishStage::Funding);
+        let (client, controller) = get_funded_controller(&vault.storage).await?;
+        let (doc, frag) = create_did_document_with_progress(&client, &vault.storage, progress).await?;
+
+        // Save to disk for persistence between server restarts.
+        // The fragment and controller key go first: the document file marks the identity as complete.
+        write_atomic(Path::new(fragment_file), frag.as_bytes()).map_err(|e| Error::Storage(e.into()))?;
+        let controller_json = serde_json::to_string_pretty(&controller).map_err(|e| Error::Storage(e.into()))?;
+        write_atomic(&controller_file(doc_file), controller_json.as_bytes()).map_err(|e| Error::Storage(e.into()))?;
+        save_did_document(doc_file, &doc)?;
+        Ok((doc, frag))
+    } else {
+        let doc = load_did_document(doc_file)?;
+        let frag = fs::read_to_string(fragment_file)
+            .map_err(|e| Error::Storage(e.into()))?
+            .trim()
+            .to_string();
+        Ok((doc, frag))
+    }
+}
+
+/// Same as [`create_or_load_did`] for the identity's [`IdentityFiles`].
+pub async fn create_or_load_identity(files: &IdentityFiles) -> Result<(IotaDocument, String)> {
+    create_or_load_did(&files.doc_file, &files.fragment_file, &files.stronghold_path).await
+}
+
+fn load_did_document(doc_file: &str) -> Result<IotaDocument> {
+    let doc_json = fs::read_to_string(doc_file).map_err(|e| Error::Storage(e.into()))?;
+    IotaDocument::from_json(&doc_json).map_err(|e| Error::InvalidDocument(e.into()))
+}
+
+/// Loads a DID document that an earlier step must already have created.
+pub fn load_existing_did(doc_file: &str, role: &str) -> Result<IotaDocument> {
+    if !Path::new(doc_file).exists() {
+        return Err(Error::NotFound(format!("{} DID has not been created yet", role)));
+    }
+    load_did_document(doc_file)
+}
+
+/// Loads the key controlling a DID created by [`create_or_load_did`].
+pub fn load_controller_key(doc_file: &str) -> Result<ControllerKey> {
+    let path = controller_file(doc_file);
+    let json = fs::read_to_string(&path).map_err(|_| {
+        Error::NotFound(format!("no controller key recorded for {}; the DID cannot be updated", doc_file))
+    })?;
+    serde_json::from_str(&json).map_err(|e| Error::Storage(e.into()))
+}
+
+/// Replaces a stored DID document after an update was published.
+pub fn save_did_document(doc_file: &str, doc: &IotaDocument) -> Result<()> {
+    let doc_json = doc.to_json().map_err(|e| Error::InvalidDocument(e.into()))?;
+    write_atomic(Path::new(doc_file), doc_json.as_bytes()).map_err(|e| Error::Storage(e.into()))
+}
@@ -0,0 +1,630 @@
+//! Typed credential subjects for the workshop scenarios.
+//!
+//! Each subject serializes to the JSON claims of its credential type, so the same struct is used to
+//! issue a credential and to read the claims back out of a verified one.
+
+use identity_iota::core::Timestamp;
+use identity_iota::credential::Subject;
+use identity_iota::did::CoreDID;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// A credential subject with a fixed credential type and its own validation rules.
+pub trait TypedSubject: Serialize + DeserializeOwned {
+    /// The credential type (besides `VerifiableCredential`) carrying this subject.
+    const CREDENTIAL_TYPE: &'static str;
+
+    /// Checks the claims beyond what their Rust types already guarantee.
+    fn validate(&self) -> Result<()>;
+
+    /// Checks that the dates in the claims do not lie in the future. Only run when a subject is built
+    /// for issuance: a credential issued a moment ago must still decode on a verifier whose clock is
+    /// slightly behind.
+    fn validate_dates(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Converts the subject into the credential subject used by `CredentialBuilder`.
+    fn to_subject(&self) -> Result<Subject> {
+        self.validate()?;
+        self.validate_dates()?;
+        let json = serde_json::to_value(self).map_err(|e| Error::InvalidSubject(e.to_string()))?;
+        Subject::from_json_value(json).map_err(|e| Error::InvalidSubject(e.to_string()))
+    }
+
+    /// Reads the subject back from the claims of a (verified) credential. Dates are not checked, see
+    /// [`TypedSubject::validate_dates`].
+    fn from_subject(subject: &Subject) -> Result<Self> {
+        let json = serde_json::to_value(subject).map_err(|e| Error::InvalidSubject(e.to_string()))?;
+        let typed: Self = serde_json::from_value(json)
+            .map_err(|e| Error::InvalidSubject(format!("not a {} subject: {}", Self::CREDENTIAL_TYPE, e)))?;
+        typed.validate()?;
+        Ok(typed)
+    }
+}
+
+fn require_did(field: &str, value: &str) -> Result<()> {
+    CoreDID::parse(value)
+        .map(|_| ())
+        .map_err(|e| Error::InvalidSubject(format!("{} must be a DID: {}", field, e)))
+}
+
+fn require_non_empty(field: &str, value: &str) -> Result<()> {
+    if value.trim().is_empty() {
+        return Err(Error::InvalidSubject(format!("{} must not be empty", field)));
+    }
+    Ok(())
+}
+
+fn require<T>(field: &str, value: Option<T>) -> Result<T> {
+    value.ok_or_else(|| Error::InvalidSubject(format!("{} is required", field)))
+}
+
+fn require_past(field: &str, value: Timestamp) -> Result<()> {
+    if value > Timestamp::now_utc() {
+        return Err(Error::InvalidSubject(format!("{} {} lies in the future", field, value)));
+    }
+    Ok(())
+}
+
+// --- University Degree ---
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Degree {
+    /// Kind of degree, e.g. `BachelorDegree`.
+    #[serde(rename = "type")]
+    pub degree_type: String,
+    pub name: String,
+}
+
+/// Subject of a `UniversityDegreeCredential`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DegreeCredentialSubject {
+    /// DID of the graduate.
+    pub id: String,
+    pub name: String,
+    pub degree: Degree,
+    #[serde(rename = "GPA", default, skip_serializing_if = "Option::is_none")]
+    pub gpa: Option<String>,
+}
+
+impl DegreeCredentialSubject {
+    pub fn builder() -> DegreeCredentialSubjectBuilder {
+        DegreeCredentialSubjectBuilder::default()
+    }
+}
+
+impl TypedSubject for DegreeCredentialSubject {
+    const CREDENTIAL_TYPE: &'static str = "UniversityDegreeCredential";
+
+    fn validate(&self) -> Result<()> {
+        require_did("id", &self.id)?;
+        require_non_empty("name", &self.name)?;
+        require_non_empty("degree.type", &self.degree.degree_type)?;
+        require_non_empty("degree.name", &self.degree.name)?;
+        if let Some(gpa) = &self.gpa {
+            match gpa.parse::<f32>() {
+                Ok(value) if (0.0..=4.0).contains(&value) => {}
+                _ => return Err(Error::InvalidSubject(format!("GPA must be between 0.0 and 4.0, got {}", gpa))),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DegreeCredentialSubjectBuilder {
+    id: Option<String>,
+    name: Option<String>,
+    degree: Option<Degree>,
+    gpa: Option<String>,
+}
+
+impl DegreeCredentialSubjectBuilder {
+    pub fn id(mut self, did: impl Into<String>) -> Self {
+        self.id = Some(did.into());
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn degree(mut self, degree_type: impl Into<String>, name: impl Into<String>) -> Self {
+        self.degree = Some(Degree {
+            degree_type: degree_type.into(),
+            name: name.into(),
+        });
+        self
+    }
+
+    pub fn gpa(mut self, gpa: impl Into<String>) -> Self {
+        self.gpa = Some(gpa.into());
+        self
+    }
+
+    /// Builds and validates the subject.
+    pub fn build(self) -> Result<DegreeCredentialSubject> {
+        let subject = DegreeCredentialSubject {
+            id: require("id", self.id)?,
+            name: require("name", self.name)?,
+            degree: require("degree", self.degree)?,
+            gpa: self.gpa,
+        };
+        subject.validate()?;
+        Ok(subject)
+    }
+}
+
+// --- Product Origin ---
+
+/// Subject of a `ProductOriginCredential`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductOriginSubject {
+    /// DID of the party holding the product, e.g. the logistics provider.
+    pub id: String,
+    #[serde(rename = "productID")]
+    pub product_id: String,
+    #[serde(rename = "factoryOrigin")]
+    pub factory_origin: String,
+    #[serde(rename = "productionDate")]
+    pub production_date: Timestamp,
+}
+
+impl ProductOriginSubject {
+    pub fn builder() -> ProductOriginSubjectBuilder {
+        ProductOriginSubjectBuilder::default()
+    }
+}
+
+impl TypedSubject for ProductOriginSubject {
+    const CREDENTIAL_TYPE: &'static str = "ProductOriginCredential";
+
+    fn validate(&self) -> Result<()> {
+        require_did("id", &self.id)?;
+        require_non_empty("productID", &self.product_id)?;
+        require_non_empty("factoryOrigin", &self.factory_origin)
+    }
+
+    fn validate_dates(&self) -> Result<()> {
+        require_past("productionDate", self.production_date)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ProductOriginSubjectBuilder {
+    id: Option<String>,
+    product_id: Option<String>,
+    factory_origin: Option<String>,
+    production_date: Option<Timestamp>,
+}
+
+impl ProductOriginSubjectBuilder {
+    pub fn id(mut self, did: impl Into<String>) -> Self {
+        self.id = Some(did.into());
+        self
+    }
+
+    pub fn product_id(mut self, product_id: impl Into<String>) -> Self {
+        self.product_id = Some(product_id.into());
+        self
+    }
+
+    pub fn factory_origin(mut self, factory_origin: impl Into<String>) -> Self {
+        self.factory_origin = Some(factory_origin.into());
+        self
+    }
+
+    /// Defaults to the time the subject is built.
+    pub fn production_date(mut self, production_date: Timestamp) -> Self {
+        self.production_date = Some(production_date);
+        self
+    }
+
+    /// Builds and validates the subject.
+    pub fn build(self) -> Result<ProductOriginSubject> {
+        let subject = ProductOriginSubject {
+            id: require("id", self.id)?,
+            product_id: require("productID", self.product_id)?,
+            factory_origin: require("factoryOrigin", self.factory_origin)?,
+            production_date: self.production_date.unwrap_or_else(Timestamp::now_utc),
+        };
+        subject.validate()?;
+        subject.validate_dates()?;
+        Ok(subject)
+    }
+}
+
+// --- Custody Handover ---
+
+/// Subject of a `CustodyHandoverCredential`, issued by the party handing a product over to the next custodian.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustodyHandoverSubject {
+    /// DID of the party receiving the product.
+    pub id: String,
+    #[serde(rename = "productID")]
+    pub product_id: String,
+    /// DID of the party handing the product over; must be the issuer of the credential.
+    #[serde(rename = "previousCustodian")]
+    pub previous_custodian: String,
+    /// Position of this handover in the chain, starting at 1 after the product origin.
+    pub sequence: u32,
+    #[serde(rename = "handoverDate")]
+    pub handover_date: Timestamp,
+}
+
+impl CustodyHandoverSubject {
+    pub fn builder() -> CustodyHandoverSubjectBuilder {
+        CustodyHandoverSubjectBuilder::default()
+    }
+}
+
+impl TypedSubject for CustodyHandoverSubject {
+    const CREDENTIAL_TYPE: &'static str = "CustodyHandoverCredential";
+
+    fn validate(&self) -> Result<()> {
+        require_did("id", &self.id)?;
+        require_did("previousCustodian", &self.previous_custodian)?;
+        require_non_empty("productID", &self.product_id)?;
+        if self.id == self.previous_custodian {
+            return Err(Error::InvalidSubject("a product cannot be handed over to its current custodian".to_string()));
+        }
+        if self.sequence == 0 {
+            return Err(Error::InvalidSubject("sequence starts at 1".to_string()));
+        }
+        Ok(())
+    }
+
+    fn validate_dates(&self) -> Result<()> {
+        require_past("handoverDate", self.handover_date)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CustodyHandoverSubjectBuilder {
+    id: Option<String>,
+    product_id: Option<String>,
+    previous_custodian: Option<String>,
+    sequence: Option<u32>,
+    handover_date: Option<Timestamp>,
+}
+
+impl CustodyHandoverSubjectBuilder {
+    pub fn id(mut self, did: impl Into<String>) -> Self {
+        self.id = Some(did.into());
+        self
+    }
+
+    pub fn product_id(mut self, product_id: impl Into<String>) -> Self {
+        self.product_id = Some(product_id.into());
+        self
+    }
+
+    pub fn previous_custodian(mut self, did: impl Into<String>) -> Self {
+        self.previous_custodian = Some(did.into());
+        self
+    }
+
+    pub fn sequence(mut self, sequence: u32) -> Self {
+        self.sequence = Some(sequence);
+        self
+    }
+
+    /// Defaults to the time the subject is built.
+    pub fn handover_date(mut self, handover_date: Timestamp) -> Self {
+        self.handover_date = Some(handover_date);
+        self
+    }
+
+    /// Builds and validates the subject.
+    pub fn build(self) -> Result<CustodyHandoverSubject> {
+        let subject = CustodyHandoverSubject {
+            id: require("id", self.id)?,
+            product_id: require("productID", self.product_id)?,
+            previous_custodian: require("previousCustodian", self.previous_custodian)?,
+            sequence: require("sequence", self.sequence)?,
+            handover_date: self.handover_date.unwrap_or_else(Timestamp::now_utc),
+        };
+        subject.validate()?;
+        subject.validate_dates()?;
+        Ok(subject)
+    }
+}
+
+// --- Shipment Events ---
+
+/// What happened to a shipment, with the readings specific to each kind of event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "eventType", rename_all = "camelCase")]
+pub enum ShipmentEvent {
+    #[serde(rename_all = "camelCase")]
+    Pickup { location: String },
+    #[serde(rename_all = "camelCase")]
+    DropOff { location: String },
+    /// The measured temperature left the allowed range.
+    #[serde(rename_all = "camelCase")]
+    TemperatureExcursion {
+        temperature_celsius: f64,
+        min_celsius: Option<f64>,
+        max_celsius: Option<f64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    TamperSealCheck { seal_id: String, intact: bool },
+}
+
+/// Subject of a `ShipmentEventCredential`, issued by the sensor or carrier that observed the event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShipmentEventSubject {
+    #[serde(rename = "productID")]
+    pub product_id: String,
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: Timestamp,
+    #[serde(flatten)]
+    pub event: ShipmentEvent,
+}
+
+impl ShipmentEventSubject {
+    /// Creates an event that occurred now.
+    pub fn new(product_id: impl Into<String>, event: ShipmentEvent) -> Result<Self> {
+        let subject = ShipmentEventSubject {
+            product_id: product_id.into(),
+            occurred_at: Timestamp::now_utc(),
+            event,
+        };
+        subject.validate()?;
+        Ok(subject)
+    }
+
+    pub fn event_type(&self) -> &'static str {
+        match self.event {
+            ShipmentEvent::Pickup { .. } => "pickup",
+            ShipmentEvent::DropOff { .. } => "dropOff",
+            ShipmentEvent::TemperatureExcursion { .. } => "temperatureExcursion",
+            ShipmentEvent::TamperSealCheck { .. } => "tamperSealCheck",
+        }
+    }
+}
+
+impl TypedSubject for ShipmentEventSubject {
+    const CREDENTIAL_TYPE: &'static str = "ShipmentEventCredential";
+
+    fn validate(&self) -> Result<()> {
+        require_non_empty("productID", &self.product_id)?;
+        match &self.event {
+            ShipmentEvent::Pickup { location } | ShipmentEvent::DropOff { location } => {
+                require_non_empty("location", location)
+            }
+            ShipmentEvent::TemperatureExcursion {
+                temperature_celsius,
+                min_celsius,
+                max_celsius,
+            } => {
+                if min_celsius.is_none() && max_celsius.is_none() {
+                    return Err(Error::InvalidSubject(
+                        "a temperature excursion needs minCelsius or maxCelsius".to_string(),
+                    ));
+                }
+                let below = min_celsius.map_or(false, |min| *temperature_celsius < min);
+                let above = max_celsius.map_or(false, |max| *temperature_celsius > max);
+                if !below && !above {
+                    return Err(Error::InvalidSubject(format!(
+                        "{} °C is within the allowed range, which is not an excursion",
+                        temperature_celsius
+                    )));
+                }
+                Ok(())
+            }
+            ShipmentEvent::TamperSealCheck { seal_id, .. } => require_non_empty("sealId", seal_id),
+        }
+    }
+
+    fn validate_dates(&self) -> Result<()> {
+        require_past("occurredAt", self.occurred_at)
+    }
+}
+
+// --- Decoding Verified Credentials ---
+
+/// A subject of one of the known credential types, decoded from a verified credential.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnownSubject {
+    Degree(DegreeCredentialSubject),
+    ProductOrigin(ProductOriginSubject),
+    CustodyHandover(CustodyHandoverSubject),
+    ShipmentEvent(ShipmentEventSubject),
+}
+
+impl KnownSubject {
+    /// Decodes `subject` according to the first known type in `credential_types`.
+    /// Returns `None` if none of the types is known.
+    pub fn decode(credential_types: &[String], subject: &Subject) -> Option<Result<Self>> {
+        credential_types.iter().find_map(|credential_type| match credential_type.as_str() {
+            t if t == DegreeCredentialSubject::CREDENTIAL_TYPE => {
+                Some(DegreeCredentialSubject::from_subject(subject).map(KnownSubject::Degree))
+            }
+            t if t == ProductOriginSubject::CREDENTIAL_TYPE => {
+                Some(ProductOriginSubject::from_subject(subject).map(KnownSubject::ProductOrigin))
+            }
+            t if t == CustodyHandoverSubject::CREDENTIAL_TYPE => {
+                Some(CustodyHandoverSubject::from_subject(subject).map(KnownSubject::CustodyHandover))
+            }
+            t if t == ShipmentEventSubject::CREDENTIAL_TYPE => {
+                Some(ShipmentEventSubject::from_subject(subject).map(KnownSubject::ShipmentEvent))
+            }
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_iota::core::Duration;
+
+    const HOLDER: &str = "did:example:alice";
+    const CARRIER: &str = "did:example:carrier";
+
+    fn in_five_minutes() -> Timestamp {
+        Timestamp::now_utc().checked_add(Duration::minutes(5)).unwrap()
+    }
+
+    fn types(credential_type: &str) -> Vec<String> {
+        vec!["VerifiableCredential".to_string(), credential_type.to_string()]
+    }
+
+    fn origin() -> ProductOriginSubject {
+        ProductOriginSubject::builder()
+            .id(HOLDER)
+            .product_id("PKG-1")
+            .factory_origin("Berlin_Smart_Factory_01")
+            .build()
+            .unwrap()
+    }
+
+    fn handover() -> CustodyHandoverSubject {
+        CustodyHandoverSubject::builder()
+            .id(CARRIER)
+            .product_id("PKG-1")
+            .previous_custodian(HOLDER)
+            .sequence(1)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn degree_round_trips_through_the_credential_subject() {
+        let degree = DegreeCredentialSubject::builder()
+            .id(HOLDER)
+            .name("Alice")
+            .degree("BachelorDegree", "Bachelor of Science and Arts")
+            .gpa("4.0")
+            .build()
+            .unwrap();
+        let subject = degree.to_subject().unwrap();
+        assert_eq!(DegreeCredentialSubject::from_subject(&subject).unwrap(), degree);
+
+        let decoded = KnownSubject::decode(&types(DegreeCredentialSubject::CREDENTIAL_TYPE), &subject);
+        assert_eq!(decoded.unwrap().unwrap(), KnownSubject::Degree(degree));
+    }
+
+    #[test]
+    fn supply_chain_subjects_round_trip_through_the_credential_subject() {
+        let origin = origin();
+        let subject = origin.to_subject().unwrap();
+        assert_eq!(ProductOriginSubject::from_subject(&subject).unwrap(), origin);
+
+        let handover = handover();
+        let subject = handover.to_subject().unwrap();
+        assert_eq!(CustodyHandoverSubject::from_subject(&subject).unwrap(), handover);
+
+        let event = ShipmentEventSubject::new(
+            "PKG-1",
+            ShipmentEvent::TemperatureExcursion {
+                temperature_celsius: 11.5,
+                min_celsius: None,
+                max_celsius: Some(8.0),
+            },
+        )
+        .unwrap();
+        let subject = event.to_subject().unwrap();
+        assert_eq!(ShipmentEventSubject::from_subject(&subject).unwrap(), event);
+        assert_eq!(event.event_type(), "temperatureExcursion");
+    }
+
+    #[test]
+    fn rejects_invalid_degrees() {
+        let builder = || DegreeCredentialSubject::builder().id(HOLDER).name("Alice").degree("BachelorDegree", "BSc");
+
+        assert!(builder().build().is_ok());
+        assert!(builder().gpa("4.5").build().is_err());
+        assert!(builder().gpa("excellent").build().is_err());
+        assert!(builder().id("alice").build().is_err());
+        assert!(builder().name(" ").build().is_err());
+        assert!(DegreeCredentialSubject::builder().id(HOLDER).name("Alice").build().is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_handovers() {
+        let builder = || CustodyHandoverSubject::builder().id(CARRIER).product_id("PKG-1").previous_custodian(HOLDER);
+
+        assert!(builder().sequence(0).build().is_err());
+        assert!(builder().previous_custodian(CARRIER).sequence(1).build().is_err());
+        assert!(builder().build().is_err());
+    }
+
+    #[test]
+    fn rejects_shipment_events_that_are_not_excursions() {
+        let within_range = ShipmentEvent::TemperatureExcursion {
+            temperature_celsius: 5.0,
+            min_celsius: Some(2.0),
+            max_celsius: Some(8.0),
+        };
+        let no_range = ShipmentEvent::TemperatureExcursion {
+            temperature_celsius: 5.0,
+            min_celsius: None,
+            max_celsius: None,
+        };
+
+        assert!(ShipmentEventSubject::new("PKG-1", within_range).is_err());
+        assert!(ShipmentEventSubject::new("PKG-1", no_range).is_err());
+        assert!(ShipmentEventSubject::new("PKG-1", ShipmentEvent::Pickup { location: String::new() }).is_err());
+        assert!(ShipmentEventSubject::new("", ShipmentEvent::Pickup { location: "Berlin".to_string() }).is_err());
+    }
+
+    #[test]
+    fn builders_reject_dates_in_the_future() {
+        let origin = ProductOriginSubject::builder()
+            .id(HOLDER)
+            .product_id("PKG-1")
+            .factory_origin("Berlin_Smart_Factory_01")
+            .production_date(in_five_minutes())
+            .build();
+        assert!(origin.is_err());
+
+        let handover = CustodyHandoverSubject::builder()
+            .id(CARRIER)
+            .product_id("PKG-1")
+            .previous_custodian(HOLDER)
+            .sequence(1)
+            .handover_date(in_five_minutes())
+            .build();
+        assert!(handover.is_err());
+
+        let event = ShipmentEventSubject {
+            occurred_at: in_five_minutes(),
+            ..ShipmentEventSubject::new("PKG-1", ShipmentEvent::Pickup { location: "Berlin".to_string() }).unwrap()
+        };
+        assert!(event.to_subject().is_err());
+    }
+
+    #[test]
+    fn decoding_tolerates_dates_slightly_in_the_future() {
+        // A verifier whose clock is behind the issuer's still reads the credential
+        let origin = ProductOriginSubject {
+            production_date: in_five_minutes(),
+            ..origin()
+        };
+        let subject = Subject::from_json_value(serde_json::to_value(&origin).unwrap()).unwrap();
+        assert_eq!(ProductOriginSubject::from_subject(&subject).unwrap(), origin);
+    }
+
+    #[test]
+    fn decoding_still_rejects_invalid_claims() {
+        let handover = CustodyHandoverSubject { sequence: 0, ..handover() };
+        let subject = Subject::from_json_value(serde_json::to_value(&handover).unwrap()).unwrap();
+        assert!(CustodyHandoverSubject::from_subject(&subject).is_err());
+
+        let subject = origin().to_subject().unwrap();
+        assert!(DegreeCredentialSubject::from_subject(&subject).is_err());
+    }
+
+    #[test]
+    fn decode_ignores_unknown_types() {
+        let subject = origin().to_subject().unwrap();
+        assert!(KnownSubject::decode(&types("EmployeeBadgeCredential"), &subject).is_none());
+    }
+}
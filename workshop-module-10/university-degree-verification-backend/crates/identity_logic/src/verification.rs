@@ -0,0 +1,396 @@
+//! Resolution and validation steps shared by every verifier.
+//!
+//! DIDs are resolved with `did:iota` from the network and `did:key`, `did:jwk` and `did:web` through
+//! [`crate::resolution`], so holders and issuers may use any of these methods. With a
+//! [`DocumentCache`] attached, documents resolved by earlier requests are reused until they expire.
+//!
+//! Verifiers that report each step use the individual methods of [`Verifier`]; those that only need a
+//! yes or no use [`Verifier::verify_presentation`], which stops at the first failure.
+
+use identity_eddsa_verifier::EdDSAJwsVerifier;
+use identity_iota::{
+    core::Object,
+    credential::{
+        CompoundCredentialValidationError, CompoundJwtPresentationValidationError, Credential, DecodedJwtCredential,
+        DecodedJwtPresentation, FailFast, Jwt, JwtCredentialValidationOptions, JwtCredentialValidator,
+        JwtCredentialValidatorUtils, JwtPresentationValidationOptions, JwtPresentationValidator,
+        JwtPresentationValidatorUtils, SubjectHolderRelationship,
+    },
+    did::{CoreDID, DID},
+    document::{verifiable::JwsVerificationOptions, CoreDocument},
+    iota::{
+        rebased::{client::IdentityClientReadOnly, Error as IotaError},
+        IotaDID, IotaDocument,
+    },
+    resolver::Resolver,
+    verification::jws::Decoder,
+};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::OnceCell;
+
+use crate::{
+    cache::DocumentCache,
+    get_read_only_client,
+    resolution::{expand_did_jwk, expand_did_key, DidWebResolver},
+    Error, Result,
+};
+
+/// Why a presentation or one of its credentials was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum VerificationError {
+    #[error("malformed JWT: {0}")]
+    Malformed(String),
+    #[error("could not resolve {did}: {reason}")]
+    Unresolvable { did: String, reason: String },
+    #[error("invalid presentation: {0}")]
+    Presentation(String),
+    #[error("credential [{}] is invalid: {reason}", index + 1)]
+    Credential { index: usize, reason: String },
+}
+
+/// A credential whose signature, validity period and holder binding were checked.
+#[derive(Debug, Clone)]
+pub struct VerifiedCredential {
+    pub issuer: CoreDID,
+    pub credential: Credential<Object>,
+}
+
+/// A presentation whose signature, expiry and challenge were checked, with its verified credentials.
+#[derive(Debug, Clone)]
+pub struct VerifiedPresentation {
+    pub holder: CoreDID,
+    pub presentation: DecodedJwtPresentation<Jwt>,
+    pub credentials: Vec<VerifiedCredential>,
+}
+
+/// Extracts the issuer DID of a credential JWT without verifying it.
+pub fn extract_issuer(credential_jwt: &Jwt) -> Result<CoreDID, VerificationError> {
+    JwtCredentialValidatorUtils::extract_issuer_from_jwt(credential_jwt)
+        .map_err(|e| VerificationError::Malformed(format!("could not extract issuer DID: {}", e)))
+}
+
+/// Extracts the holder DID of a presentation JWT without verifying it.
+pub fn extract_holder(presentation_jwt: &Jwt) -> Result<CoreDID, VerificationError> {
+    JwtPresentationValidatorUtils::extract_holder(presentation_jwt)
+        .map_err(|e| VerificationError::Malformed(format!("could not extract holder DID: {}", e)))
+}
+
+/// Reads the nonce signed into a presentation's JWS header, without verifying the signature.
+pub fn presentation_nonce(presentation_jwt: &Jwt) -> Option<String> {
+    Decoder::new()
+        .decode_compact_serialization(presentation_jwt.as_str().as_bytes(), None)
+        .ok()
+        .and_then(|jws| jws.protected_header().and_then(|header| header.nonce()).map(str::to_owned))
+}
+
+/// DID methods [`Verifier`] resolves.
+pub const SUPPORTED_METHODS: &[&str] = &["iota", "key", "jwk", "web"];
+
+// The read-only IOTA client, connected the first time a `did:iota` document is looked up
+#[derive(Clone, Default)]
+struct IotaClient(Arc<OnceCell<IdentityClientReadOnly>>);
+
+impl IotaClient {
+    async fn get(&self) -> Result<&IdentityClientReadOnly> {
+        self.0.get_or_try_init(get_read_only_client).await
+    }
+
+    async fn resolve(&self, did: &CoreDID) -> Result<IotaDocument> {
+        let iota_did = IotaDID::parse(did.as_str()).map_err(|e| Error::InvalidDid {
+            did: did.to_string(),
+            reason: e.to_string(),
+        })?;
+        self.get().await?.resolve_did(&iota_did).await.map_err(|e| match e {
+            IotaError::DIDResolutionError(reason) => Error::NotFound(format!("{} is not published: {}", did, reason)),
+            e => Error::Resolution {
+                did: did.to_string(),
+                reason: e.to_string(),
+            },
+        })
+    }
+}
+
+/// Resolves DID documents and validates presentations and credentials against them.
+pub struct Verifier {
+    resolver: Resolver<CoreDocument>,
+    iota: IotaClient,
+    cache: Option<Arc<DocumentCache>>,
+    // Resolve every DID anew, refreshing the cache, e.g. for high-assurance checks
+    force_fresh: bool,
+}
+
+impl Default for Verifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Verifier {
+    /// A resolver with the `did:iota`, `did:key`, `did:jwk` and `did:web` handlers attached. The IOTA
+    /// network is only connected once a `did:iota` document is resolved, so DIDs of the other methods,
+    /// and documents served from the cache, resolve while the node is unreachable.
+    pub fn new() -> Self {
+        let iota = IotaClient::default();
+        let mut resolver: Resolver<CoreDocument> = Resolver::new();
+        let iota_handler = iota.clone();
+        resolver.attach_handler("iota".to_owned(), move |did: CoreDID| {
+            let iota = iota_handler.clone();
+            async move { iota.resolve(&did).await }
+        });
+        resolver.attach_handler("key".to_owned(), |did: CoreDID| async move { expand_did_key(&did) });
+        resolver.attach_handler("jwk".to_owned(), |did: CoreDID| async move { expand_did_jwk(&did) });
+        let web = DidWebResolver::from_env();
+        resolver.attach_handler("web".to_owned(), move |did: CoreDID| {
+            let web = web.clone();
+            async move { web.resolve(&did).await }
+        });
+        Verifier {
+            resolver,
+            iota,
+            cache: None,
+            force_fresh: false,
+        }
+    }
+
+    /// Serves documents from `cache` and stores the ones resolved from now on. With `force_fresh`,
+    /// every DID is resolved anew and its cached copy replaced.
+    pub fn with_cache(mut self, cache: Arc<DocumentCache>, force_fresh: bool) -> Self {
+        self.cache = Some(cache);
+        self.force_fresh = force_fresh;
+        self
+    }
+
+    fn cached(&self, did: &CoreDID) -> Option<CoreDocument> {
+        let cache = self.cache.as_ref()?;
+        if self.force_fresh {
+            cache.record_bypass();
+            return None;
+        }
+        cache.get(did)
+    }
+
+    fn remember(&self, did: &CoreDID, document: &CoreDocument) {
+        if let Some(cache) = &self.cache {
+            cache.insert(did.clone(), document.clone());
+        }
+    }
+
+    /// Resolves a `did:iota` document together with its metadata, such as whether it was deactivated.
+    /// Only a DID the network has no document for yields [`Error::NotFound`]; node and connection
+    /// failures yield [`Error::Resolution`].
+    pub async fn resolve_iota(&self, did: &CoreDID) -> Result<IotaDocument> {
+        self.iota.resolve(did).await
+    }
+
+    /// Resolves a single DID document.
+    pub async fn resolve(&self, did: &CoreDID) -> Result<CoreDocument, VerificationError> {
+        if let Some(document) = self.cached(did) {
+            return Ok(document);
+        }
+        let document = self.resolver.resolve(did).await.map_err(|e| VerificationError::Unresolvable {
+            did: did.to_string(),
+            reason: e.to_string(),
+        })?;
+        self.remember(did, &document);
+        Ok(document)
+    }
+
+    /// Resolves several DID documents, failing if any of them cannot be resolved.
+    pub async fn resolve_multiple(&self, dids: &[CoreDID]) -> Result<HashMap<CoreDID, CoreDocument>, VerificationError> {
+        let mut documents = HashMap::new();
+        let mut uncached = Vec::new();
+        for did in dids {
+            match self.cached(did) {
+                Some(document) => {
+                    documents.insert(did.clone(), document);
+                }
+                None => uncached.push(did.clone()),
+            }
+        }
+        if uncached.is_empty() {
+            return Ok(documents);
+        }
+
+        let resolved = self.resolver.resolve_multiple(&uncached).await.map_err(|e| VerificationError::Unresolvable {
+            did: uncached.iter().map(|did| did.as_str()).collect::<Vec<_>>().join(", "),
+            reason: e.to_string(),
+        })?;
+        for (did, document) in resolved {
+            self.remember(&did, &document);
+            documents.insert(did, document);
+        }
+        Ok(documents)
+    }
+
+    /// Extracts the holder DID of a presentation and resolves its document.
+    pub async fn resolve_holder(&self, presentation_jwt: &Jwt) -> Result<(CoreDID, CoreDocument), VerificationError> {
+        let holder = extract_holder(presentation_jwt)?;
+        let holder_doc = self.resolve(&holder).await?;
+        Ok((holder, holder_doc))
+    }
+
+    /// Checks the presentation signature and expiry, and its nonce if a `challenge` is given.
+    pub fn validate_presentation(
+        &self,
+        presentation_jwt: &Jwt,
+        holder_doc: &CoreDocument,
+        challenge: Option<&str>,
+    ) -> Result<DecodedJwtPresentation<Jwt>, CompoundJwtPresentationValidationError> {
+        let mut verifier_options = JwsVerificationOptions::default();
+        if let Some(challenge) = challenge {
+            verifier_options = verifier_options.nonce(challenge.to_owned());
+        }
+        let options = JwtPresentationValidationOptions::default().presentation_verifier_options(verifier_options);
+
+        JwtPresentationValidator::with_signature_verifier(EdDSAJwsVerifier::default())
+            .validate(presentation_jwt, holder_doc, &options)
+    }
+
+    /// Checks a credential's signature, validity period, status and its subject's relation to `holder`.
+    pub fn validate_credential(
+        &self,
+        credential_jwt: &Jwt,
+        issuer_doc: &CoreDocument,
+        holder: &CoreDID,
+        relationship: SubjectHolderRelationship,
+        fail_fast: FailFast,
+    ) -> Result<DecodedJwtCredential<Object>, CompoundCredentialValidationError> {
+        let options = JwtCredentialValidationOptions::default().subject_holder_relationship(holder.to_url().into(), relationship);
+
+        JwtCredentialValidator::with_signature_verifier(EdDSAJwsVerifier::default())
+            .validate(credential_jwt, issuer_doc, &options, fail_fast)
+    }
+
+    /// Checks a credential that is not part of a presentation, e.g. a sensor reading.
+    pub async fn verify_credential(&self, credential_jwt: &Jwt) -> Result<VerifiedCredential, VerificationError> {
+        let issuer = extract_issuer(credential_jwt)?;
+        let issuer_doc = self.resolve(&issuer).await?;
+        let decoded: DecodedJwtCredential<Object> = JwtCredentialValidator::with_signature_verifier(EdDSAJwsVerifier::default())
+            .validate(credential_jwt, &issuer_doc, &JwtCredentialValidationOptions::default(), FailFast::FirstError)
+            .map_err(|e| VerificationError::Credential { index: 0, reason: e.to_string() })?;
+        Ok(VerifiedCredential {
+            issuer,
+            credential: decoded.credential,
+        })
+    }
+
+    /// Verifies a presentation and every credential in it, stopping at the first failure.
+    /// The nonce is only checked if a `challenge` is given.
+    pub async fn verify_presentation(
+        &self,
+        presentation_jwt: &Jwt,
+        challenge: Option<&str>,
+        relationship: SubjectHolderRelationship,
+    ) -> Result<VerifiedPresentation, VerificationError> {
+        // 1. Resolve the holder and check the presentation it signed
+        let (holder, holder_doc) = self.resolve_holder(presentation_jwt).await?;
+        let presentation = self
+            .validate_presentation(presentation_jwt, &holder_doc, challenge)
+            .map_err(|e| VerificationError::Presentation(e.to_string()))?;
+
+        // 2. Resolve the issuers of the embedded credentials
+        let credential_jwts = &presentation.presentation.verifiable_credential;
+        let issuers = credential_jwts.iter().map(extract_issuer).collect::<Result<Vec<_>, _>>()?;
+        let issuer_docs = self.resolve_multiple(&issuers).await?;
+
+        // 3. Validate each credential against its issuer
+        let mut credentials = Vec::with_capacity(credential_jwts.len());
+        for (index, (credential_jwt, issuer)) in credential_jwts.iter().zip(issuers).enumerate() {
+            let decoded = self
+                .validate_credential(credential_jwt, &issuer_docs[&issuer], &holder, relationship, FailFast::FirstError)
+                .map_err(|e| VerificationError::Credential { index, reason: e.to_string() })?;
+            credentials.push(VerifiedCredential {
+                issuer,
+                credential: decoded.credential,
+            });
+        }
+
+        Ok(VerifiedPresentation {
+            holder,
+            presentation,
+            credentials,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use identity_iota::{
+        core::{FromJson, Url},
+        credential::{CredentialBuilder, RevocationBitmap, RevocationBitmapStatus, Subject},
+        storage::{JwkDocumentExt, JwkMemStore, JwsSignatureOptions, KeyIdMemstore},
+        verification::{jws::JwsAlgorithm, MethodScope},
+    };
+    use std::time::Duration;
+
+    use crate::{
+        resolution::{
+            tests::{local_host, serve},
+            DID_WEB_TIMEOUT,
+        },
+        MemStorage,
+    };
+
+    const REVOKED_INDEX: u32 = 5;
+
+    // A verifier sharing `cache` that fetches did:web documents from local test servers over plain HTTP
+    fn local_verifier(cache: &Arc<DocumentCache>) -> Verifier {
+        let mut verifier = Verifier::new().with_cache(cache.clone(), false);
+        let web = DidWebResolver::new(DID_WEB_TIMEOUT, true);
+        verifier.resolver.attach_handler("web".to_owned(), move |did: CoreDID| {
+            let web = web.clone();
+            async move { web.resolve(&did).await }
+        });
+        verifier
+    }
+
+    #[tokio::test]
+    async fn a_revocation_takes_effect_once_the_issuer_is_purged() {
+        // 1. A did:web issuer with a revocation bitmap signs a credential with a status in it
+        let (listener, did) = local_host().await;
+        let storage = MemStorage::new(JwkMemStore::new(), KeyIdMemstore::new());
+        let mut issuer_doc = CoreDocument::builder(Object::new()).id(did.clone()).build().unwrap();
+        let fragment = issuer_doc
+            .generate_method(&storage, JwkMemStore::ED25519_KEY_TYPE, JwsAlgorithm::EdDSA, None, MethodScope::VerificationMethod)
+            .await
+            .unwrap();
+        let service_url = did.to_url().join("#revocation").unwrap();
+        issuer_doc
+            .insert_service(RevocationBitmap::new().to_service(service_url.clone()).unwrap())
+            .unwrap();
+        let credential = CredentialBuilder::default()
+            .issuer(Url::parse(did.as_str()).unwrap())
+            .subject(Subject::from_json_value(serde_json::json!({ "id": "did:example:holder" })).unwrap())
+            .status(RevocationBitmapStatus::new(service_url.clone(), REVOKED_INDEX).into())
+            .build()
+            .unwrap();
+        let jwt = issuer_doc
+            .create_credential_jwt(&credential, &storage, &fragment, &JwsSignatureOptions::default(), None)
+            .await
+            .unwrap();
+
+        // 2. The host serves the document, then the version revoking the credential
+        let mut revoked_doc = issuer_doc.clone();
+        revoked_doc.revoke_credentials(&service_url, &[REVOKED_INDEX]).unwrap();
+        serve(
+            listener,
+            vec![
+                Some(serde_json::to_string(&issuer_doc).unwrap()),
+                Some(serde_json::to_string(&revoked_doc).unwrap()),
+            ],
+        );
+
+        // 3. Verifies, and the issuer document is cached
+        let cache = Arc::new(DocumentCache::new(Duration::from_secs(60), 10));
+        let verifier = local_verifier(&cache);
+        verifier.verify_credential(&jwt).await.unwrap();
+        assert_eq!(cache.get(&did), Some(issuer_doc));
+
+        // 4. The publish paths purge the issuer once the revocation is out, so the next check sees it
+        assert_eq!(cache.purge(Some(&did)), 1);
+        let revoked = verifier.verify_credential(&jwt).await;
+        assert!(matches!(revoked, Err(VerificationError::Credential { .. })), "a revoked credential verified");
+        assert_eq!(cache.get(&did), Some(revoked_doc));
+    }
+}
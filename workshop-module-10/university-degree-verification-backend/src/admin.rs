@@ -0,0 +1,61 @@
+//! Access control for the `/api/admin` routes, which change what the verifier trusts and caches.
+
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use std::sync::Arc;
+
+use crate::error::ApiError;
+
+/// Requires admin requests to carry `Authorization: Bearer <ADMIN_TOKEN>`. Without `ADMIN_TOKEN`
+/// the admin API is disabled.
+#[derive(Debug, Clone, Default)]
+pub struct AdminGuard {
+    token: Option<Arc<str>>,
+}
+
+impl AdminGuard {
+    pub fn from_env() -> Self {
+        let token = std::env::var("ADMIN_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .map(Arc::from);
+        AdminGuard { token }
+    }
+
+    /// Puts every route of `router` behind the admin token.
+    pub fn protect(&self, router: Router) -> Router {
+        router.route_layer(middleware::from_fn_with_state(self.clone(), require_admin))
+    }
+}
+
+// Compares without stopping at the first differing byte, so response times do not reveal the token
+fn token_matches(expected: &str, presented: &str) -> bool {
+    expected.len() == presented.len()
+        && expected
+            .bytes()
+            .zip(presented.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+async fn require_admin(State(guard): State<AdminGuard>, request: Request, next: Next) -> Result<Response, ApiError> {
+    let Some(token) = &guard.token else {
+        return Err(ApiError::Unauthorized(
+            "the admin API is disabled; set ADMIN_TOKEN to enable it".to_string(),
+        ));
+    };
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|authorization| authorization.to_str().ok())
+        .and_then(|authorization| authorization.strip_prefix("Bearer "));
+    match presented {
+        Some(presented) if token_matches(token, presented) => Ok(next.run(request).await),
+        _ => Err(ApiError::Unauthorized("missing or invalid admin token".to_string())),
+    }
+}
@@ -0,0 +1,225 @@
+use axum::{
+    extract::{Json, Path as UrlPath, Query, State},
+    http::{header::CONTENT_TYPE, HeaderMap},
+};
+use identity_logic::store::open_vault;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{
+    error::ApiError,
+    revocation::publish_revocation_service,
+    templates::{IssueFromTemplate, IssuedCredential, TemplateIssuer, ISSUER_DOC_FILE, ISSUER_STRONGHOLD_PATH},
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchOptions {
+    // Publish the template's revocation service in one DID update once all rows are signed
+    #[serde(default)]
+    publish_revocation_bitmap: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchRequest {
+    rows: Vec<IssueFromTemplate>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchRowResult {
+    row: usize,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    credential: Option<IssuedCredential>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RevocationUpdate {
+    service: String,
+    published: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResponse {
+    template: String,
+    issued: usize,
+    failed: usize,
+    results: Vec<BatchRowResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revocation_update: Option<RevocationUpdate>,
+}
+
+// Parses a CSV upload: `subjectDid` and `validityDays` columns are optional, every other column is a claim.
+// Cells holding a JSON object or array (e.g. a degree) are parsed as JSON, all others are strings.
+fn parse_csv(body: &str) -> Result<Vec<Result<IssueFromTemplate, String>>, ApiError> {
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| ApiError::InvalidInput(format!("invalid CSV header: {}", e)))?
+        .clone();
+
+    let rows = reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|e| e.to_string())?;
+            let mut request = IssueFromTemplate::default();
+            for (column, value) in headers.iter().zip(record.iter()) {
+                let value = value.trim();
+                if value.is_empty() {
+                    continue;
+                }
+                match column.trim() {
+                    "subjectDid" => request.subject_did = Some(value.to_string()),
+                    "validityDays" => {
+                        let days = value.parse().map_err(|_| format!("validityDays {} is not a number", value))?;
+                        request.validity_days = Some(days);
+                    }
+                    claim => {
+                        let value = if value.starts_with('{') || value.starts_with('[') {
+                            serde_json::from_str(value).map_err(|e| format!("column {} is not valid JSON: {}", claim, e))?
+                        } else {
+                            serde_json::Value::String(value.to_string())
+                        };
+                        request.claims.insert(claim.to_string(), value);
+                    }
+                }
+            }
+            Ok(request)
+        })
+        .collect();
+    Ok(rows)
+}
+
+// Issues one credential per row, as JSON `{ "rows": [...] }` or a `text/csv` upload, in a single vault session
+pub async fn issue_batch(
+    State(state): State<Arc<AppState>>,
+    UrlPath(template_name): UrlPath<String>,
+    Query(options): Query<BatchOptions>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<BatchResponse>, ApiError> {
+    // 1. Parse the rows; a malformed row fails on its own instead of failing the batch
+    let is_csv = headers
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .map_or(false, |content_type| content_type.starts_with("text/csv"));
+    let rows: Vec<Result<IssueFromTemplate, String>> = if is_csv {
+        parse_csv(&body)?
+    } else {
+        let request: BatchRequest = serde_json::from_str(&body)
+            .map_err(|e| ApiError::InvalidInput(format!("invalid batch request: {}", e)))?;
+        request.rows.into_iter().map(Ok).collect()
+    };
+    if rows.is_empty() {
+        return Err(ApiError::InvalidInput("the batch contains no rows".to_string()));
+    }
+
+    // 2. Load the template and issuer; the revocation service may be published at the end
+    let issuer = TemplateIssuer::new(&state, &template_name, options.publish_revocation_bitmap).await?;
+    if options.publish_revocation_bitmap && issuer.revocation_service.is_none() {
+        return Err(ApiError::InvalidInput(format!(
+            "template {} does not use a revocation bitmap",
+            template_name
+        )));
+    }
+
+    // 3. Sign every row while holding the issuer vault once
+    let vault = open_vault(ISSUER_STRONGHOLD_PATH).await?;
+    let mut results = Vec::with_capacity(rows.len());
+    for (index, row) in rows.into_iter().enumerate() {
+        let outcome = match row {
+            Ok(request) => issuer.issue(&vault, &request).await.map_err(|e| {
+                e.log();
+                e.detail()
+            }),
+            Err(e) => Err(e),
+        };
+        results.push(match outcome {
+            Ok(credential) => BatchRowResult {
+                row: index + 1,
+                success: true,
+                credential: Some(credential),
+                error: None,
+            },
+            Err(error) => BatchRowResult {
+                row: index + 1,
+                success: false,
+                credential: None,
+                error: Some(error),
+            },
+        });
+    }
+    let issued = results.iter().filter(|result| result.success).count();
+
+    // 4. Publish the revocation service in one DID update, if requested
+    let mut revocation_update = None;
+    if let (true, Some(service_url)) = (options.publish_revocation_bitmap && issued > 0, &issuer.revocation_service) {
+        let outcome = publish_revocation_service(&vault, &state.did_cache, ISSUER_DOC_FILE, &issuer.issuer_doc, service_url).await;
+        revocation_update = Some(RevocationUpdate {
+            service: service_url.to_string(),
+            published: matches!(outcome, Ok(true)),
+            error: outcome.err().map(|e| {
+                e.log();
+                e.detail()
+            }),
+        });
+    }
+
+    Ok(Json(BatchResponse {
+        template: template_name,
+        issued,
+        failed: results.len() - issued,
+        results,
+        revocation_update,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_reserved_columns_and_claims() {
+        let rows = parse_csv(
+            "subjectDid,name,degree,validityDays\n\
+             did:example:alice,Alice,\"{\"\"type\"\":\"\"BachelorDegree\"\"}\",365\n\
+             ,Bob,,\n",
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let alice = rows[0].as_ref().unwrap();
+        assert_eq!(alice.subject_did.as_deref(), Some("did:example:alice"));
+        assert_eq!(alice.validity_days, Some(365));
+        assert_eq!(alice.claims["name"], "Alice");
+        assert_eq!(alice.claims["degree"], serde_json::json!({ "type": "BachelorDegree" }));
+
+        // Empty cells fall back to the template defaults
+        let bob = rows[1].as_ref().unwrap();
+        assert_eq!(bob.subject_did, None);
+        assert_eq!(bob.validity_days, None);
+        assert_eq!(bob.claims.len(), 1);
+    }
+
+    #[test]
+    fn a_malformed_row_fails_on_its_own() {
+        let rows = parse_csv("name,validityDays,courses\nAlice,soon,\nBob,30,[1,\nCarol,30,\n").unwrap();
+        assert_eq!(rows[0].as_ref().unwrap_err(), "validityDays soon is not a number");
+        assert!(rows[1].as_ref().unwrap_err().starts_with("column courses is not valid JSON"));
+        assert_eq!(rows[2].as_ref().unwrap().claims["name"], "Carol");
+    }
+
+    #[test]
+    fn header_only_has_no_rows() {
+        assert!(parse_csv("subjectDid,name\n").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+use anyhow::Result;
+use iota_identity_backend::{app, Scenario};
+
+// Serves only the supply-chain scenario, on the port the workshop exercise uses
+#[tokio::main]
+async fn main() -> Result<()> {
+    let app = app(&[Scenario::SupplyChain])?;
+
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:3002").await?;
+    println!("🚀 Supply Chain Solution running on http://0.0.0.0:3002");
+    axum::serve(listener, app.into_make_service()).await?;
+    Ok(())
+}
@@ -0,0 +1,344 @@
+//! The university degree scenario: a holder, an issuer and a verifier on top of `identity_logic`.
+
+use axum::{
+    routing::{delete, get, post},
+    extract::{State, Json},
+    http::StatusCode,
+    Router,
+};
+use identity_iota::{
+    core::{Duration, Timestamp, Url},
+    credential::{Jwt, JwtPresentationOptions},
+    did::{CoreDID, DID},
+};
+use identity_logic::{
+    issuance::{build_credential, credential_builder, sign_credential},
+    presentation::create_presentation,
+    store::{create_or_load_did, load_existing_did, open_vault},
+    subjects::{DegreeCredentialSubject, TypedSubject},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{
+    admin::AdminGuard,
+    batch::issue_batch,
+    domain_linkage::{did_configuration, verify_domain_linkage, VerifiedOrigin},
+    error::ApiError,
+    jobs::{job_events, job_status, start_create_did, start_deactivate_did, start_publish_revocation, start_update_did},
+    pipeline::{verify_presentation, VerifierRules, VerifyOptions},
+    policy::{HolderBinding, VerifierPolicy},
+    report::{Check, ErrorCode, VerificationReport},
+    schema::get_schema,
+    templates::{issue_from_template, list_templates},
+    trust_registry::TrustRegistry,
+    wallet::{delete_credential, list_credentials, store_credential, StoredCredential},
+    AppState,
+};
+
+
+// --- Data Structures for API Communication ---
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageId {
+    package_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VcJwt {
+    package_id: String,
+    // A VC JWT pasted by the client, presented alongside any stored credentials
+    vc_jwt: Option<String>,
+    // IDs of credentials in the holder wallet to include in the presentation
+    #[serde(default)]
+    credential_ids: Vec<String>,
+    // Minutes until the presentation expires (defaults to 10)
+    expires_in_minutes: Option<i64>,
+    // The verifier's DID or URL the presentation is addressed to (defaults to our own verifier)
+    audience: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VpJwt {
+    package_id: String,
+    vp_jwt: String,
+    #[serde(default)]
+    check_domain_linkage: bool,
+    // Validate every credential and report all failures instead of stopping at the first one
+    #[serde(default)]
+    collect_all_errors: bool,
+    expected_origin: Option<String>,
+    // Resolve the holder and issuers from the network instead of the DID cache
+    #[serde(default)]
+    force_fresh: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DidResponse {
+    did: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JwtResponse {
+    jwt: String,
+}
+
+// --- Verifier Rules ---
+
+// Challenge the verifier expects presentations to be signed with
+const VERIFIER_CHALLENGE: &str = "challenge-123";
+
+// The verifier policy, and the trust registry as it was when the request arrived
+struct DegreeRules<'a> {
+    policy: &'a VerifierPolicy,
+    trust_registry: TrustRegistry,
+}
+
+impl VerifierRules for DegreeRules<'_> {
+    fn challenge(&self) -> &str {
+        VERIFIER_CHALLENGE
+    }
+
+    fn audience(&self) -> Option<&str> {
+        Some(self.policy.audience())
+    }
+
+    fn max_presentation_lifetime_minutes(&self) -> Option<i64> {
+        self.policy.max_presentation_lifetime_minutes()
+    }
+
+    fn holder_binding(&self, credential_types: &[String]) -> HolderBinding {
+        self.policy.holder_binding(credential_types)
+    }
+
+    fn trust(&self, credential_types: &[String], issuer: &CoreDID) -> Result<String, String> {
+        if self.trust_registry.is_trusted(credential_types, issuer) {
+            Ok(format!("{} is trusted for {:?}", issuer, credential_types))
+        } else {
+            Err(format!("{} is not trusted for {:?}", issuer, credential_types))
+        }
+    }
+}
+
+// --- API Handlers ---
+
+// Step 1: Holder creates their DID (runs only once per holder.stronghold file)
+async fn holder_create_did(Json(_body): Json<PackageId>) -> Result<Json<DidResponse>, ApiError> {
+    let holder_doc_file = "./holder_doc.json";
+    let holder_fragment_file = "./holder_fragment.txt";
+    let stronghold_path = "./holder.stronghold";
+
+    // This calls create_or_load_did. If the file exists, it loads the existing DID.
+    // The DID is anchored and funded if newly created.
+    let (holder_doc, _) = create_or_load_did(
+        holder_doc_file,
+        holder_fragment_file,
+        stronghold_path
+    ).await?;
+
+    Ok(Json(DidResponse {
+        did: holder_doc.id().to_string(),
+    }))
+}
+
+// Step 2: Issuer creates DID (if needed) and issues a VC to the Holder
+async fn issuer_issue_vc(
+    State(state): State<Arc<AppState>>,
+    Json(_body): Json<PackageId>,
+) -> Result<Json<JwtResponse>, ApiError> {
+    let issuer_doc_file = "./issuer_doc.json";
+    let issuer_fragment_file = "./issuer_fragment.txt";
+    let issuer_stronghold_path = "./issuer.stronghold";
+
+    // 1. Create/Load Issuer DID
+    let (issuer_doc, issuer_fragment) = create_or_load_did(
+        issuer_doc_file,
+        issuer_fragment_file,
+        issuer_stronghold_path,
+    ).await?;
+
+    // 2. Load Holder DID (must exist from Step 1)
+    let holder_doc = load_existing_did("./holder_doc.json", "holder")?;
+
+    // 3. Build the degree subject and check it against the type's schema before anything is signed
+    let credential_type = DegreeCredentialSubject::CREDENTIAL_TYPE;
+    let degree = DegreeCredentialSubject::builder()
+        .id(holder_doc.id().as_str())
+        .name("Alice")
+        .degree("BachelorDegree", "Bachelor of Science and Arts")
+        .gpa("4.0")
+        .build()?;
+    let subject_json = serde_json::to_value(&degree).map_err(ApiError::identity)?;
+    let schema = state
+        .schemas
+        .get(credential_type)
+        .ok_or_else(|| ApiError::Configuration(anyhow::anyhow!("no schema registered for {}", credential_type)))?;
+    schema.validate(&subject_json).map_err(|violations| {
+        ApiError::InvalidInput(format!("subject does not conform to {}: {}", schema.id(), violations.join("; ")))
+    })?;
+
+    // 4. Build VC
+    let credential = build_credential(
+        credential_builder("https://example.edu/credentials/3732", &issuer_doc, &degree)?.schema(schema.reference()),
+    )?;
+
+    // 5. Sign VC
+    let issuer_vault = open_vault(issuer_stronghold_path).await?;
+    let credential_jwt = sign_credential(&issuer_doc, &issuer_vault.storage, &issuer_fragment, &credential).await?;
+    
+    // 6. Deliver the VC to the holder wallet
+    let stored = StoredCredential::from_jwt(credential_jwt.as_str())?;
+    state.wallet.write().await.store(stored).map_err(ApiError::storage)?;
+
+    // Return VC JWT string
+    Ok(Json(JwtResponse {
+        jwt: credential_jwt.as_str().to_string(),
+    }))
+}
+
+// Step 3: Holder creates a Verifiable Presentation (VP)
+async fn holder_create_vp(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<VcJwt>,
+) -> Result<Json<JwtResponse>, ApiError> {
+    let stronghold_path = "./holder.stronghold";
+    let holder_doc_file = "./holder_doc.json";
+    let holder_fragment_file = "./holder_fragment.txt";
+
+    // 1. Load Holder DID
+    let (holder_doc, holder_fragment) = create_or_load_did(
+        holder_doc_file,
+        holder_fragment_file,
+        stronghold_path,
+    ).await?;
+
+    let holder_vault = open_vault(stronghold_path).await?;
+    
+    let expires_in_minutes = body.expires_in_minutes.unwrap_or(10);
+    if expires_in_minutes <= 0 {
+        return Err(ApiError::InvalidInput("expiresInMinutes must be positive".to_string()));
+    }
+    let expires = Timestamp::now_utc()
+        .checked_add(Duration::minutes(expires_in_minutes))
+        .ok_or_else(|| ApiError::InvalidInput("expiresInMinutes is too large".to_string()))?;
+
+    let audience = body.audience.as_deref().unwrap_or(state.verifier_policy.audience());
+    let audience = Url::parse(audience)
+        .map_err(|e| ApiError::InvalidInput(format!("invalid audience {}: {}", audience, e)))?;
+    let presentation_options = JwtPresentationOptions::default().expiration_date(expires).audience(audience);
+    
+    // 2. Collect the credentials to present from the wallet (and the request, if pasted)
+    let mut credentials: Vec<Jwt> = Vec::new();
+    {
+        let wallet = state.wallet.read().await;
+        for id in &body.credential_ids {
+            let stored = wallet
+                .get(id)
+                .ok_or_else(|| ApiError::NotFound(format!("credential {} not found in wallet", id)))?;
+            credentials.push(Jwt::new(stored.jwt.clone()));
+        }
+    }
+    if let Some(vc_jwt) = body.vc_jwt {
+        credentials.push(Jwt::new(vc_jwt));
+    }
+
+    // 3. Build and sign the VP
+    let vp_jwt: Jwt = create_presentation(
+        &holder_doc,
+        &holder_vault.storage,
+        &holder_fragment,
+        credentials,
+        VERIFIER_CHALLENGE,
+        &presentation_options,
+    )
+    .await?;
+
+    // Return VP JWT string
+    Ok(Json(JwtResponse {
+        jwt: vp_jwt.as_str().to_string(),
+    }))
+}
+
+// Step 4: Verifier validates the VP and embedded VC
+async fn verifier_validate(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<VpJwt>,
+) -> (StatusCode, Json<VerificationReport>) {
+    let vp_jwt = Jwt::new(body.vp_jwt);
+    let mut report = VerificationReport::new();
+
+    let expected_origin = match body.expected_origin.as_deref().map(Url::parse).transpose() {
+        Ok(origin) => origin,
+        Err(e) => {
+            report.fail(Check::DomainLinkage, None, ErrorCode::DomainLinkageInvalid, format!("invalid expected origin: {}", e));
+            return (StatusCode::BAD_REQUEST, Json(report.finish()));
+        }
+    };
+
+    // 1. Check the presentation and its credentials under the verifier policy and the trust registry
+    let rules = DegreeRules {
+        policy: &state.verifier_policy,
+        trust_registry: state.trust_registry.read().await.clone(),
+    };
+    let options = VerifyOptions {
+        collect_all_errors: body.collect_all_errors,
+        force_fresh: body.force_fresh,
+    };
+    let issuers_documents = match verify_presentation(&vp_jwt, &rules, &state.schemas, &state.did_cache, options, &mut report).await {
+        Ok(issuers_documents) => issuers_documents,
+        Err(status) => return (status, Json(report.finish())),
+    };
+
+    // 2. Optionally check that each issuer DID is linked to its web domain
+    if body.check_domain_linkage {
+        for (issuer_did, issuer_doc) in &issuers_documents {
+            match verify_domain_linkage(issuer_doc, expected_origin.as_ref()).await {
+                Ok(origin) => {
+                    report.pass(Check::DomainLinkage, None, format!("issuer {} is linked to {}", issuer_did, origin));
+                    report.verified_origins.push(VerifiedOrigin {
+                        issuer: issuer_did.to_string(),
+                        origin: origin.to_string(),
+                    });
+                },
+                Err(e) => {
+                    report.fail(Check::DomainLinkage, None, ErrorCode::DomainLinkageInvalid, format!("issuer {}: {:#}", issuer_did, e));
+                }
+            }
+        }
+    }
+
+    (StatusCode::OK, Json(report.finish()))
+}
+
+/// Routes of the degree scenario, including the issuer, wallet and job APIs it relies on.
+/// The jobs changing published DIDs require the admin token.
+pub(crate) fn router(state: Arc<AppState>, admin: &AdminGuard) -> Router {
+    let admin_routes = Router::new()
+        .route("/api/jobs/update-did", post(start_update_did))
+        .route("/api/jobs/deactivate-did", post(start_deactivate_did))
+        .route("/api/jobs/publish-revocation", post(start_publish_revocation))
+        .with_state(state.clone());
+
+    Router::new()
+        .route("/api/holder/create-did", post(holder_create_did))
+        .route("/api/issuer/issue-vc", post(issuer_issue_vc))
+        .route("/api/issuer/templates", get(list_templates))
+        .route("/api/issuer/:template/issue", post(issue_from_template))
+        .route("/api/issuer/:template/issue-batch", post(issue_batch))
+        .route("/api/holder/create-vp", post(holder_create_vp))
+        .route("/api/holder/credentials", get(list_credentials).post(store_credential))
+        .route("/api/holder/credentials/:id", delete(delete_credential))
+        .route("/api/verifier/validate", post(verifier_validate))
+        .route("/api/jobs/create-did", post(start_create_did))
+        .route("/api/jobs/:id", get(job_status))
+        .route("/api/jobs/:id/events", get(job_events))
+        .route("/api/schemas/:credential_type", get(get_schema))
+        .route("/.well-known/did-configuration.json", get(did_configuration))
+        .with_state(state)
+        .merge(admin.protect(admin_routes))
+}
@@ -0,0 +1,69 @@
+//! The DID document cache shared by every verifier, and the admin API to inspect and purge it.
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use identity_iota::did::CoreDID;
+use identity_logic::cache::{CacheStats, DocumentCache};
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+
+use crate::error::ApiError;
+
+const DEFAULT_TTL_SECONDS: u64 = 300;
+const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match std::env::var(name) {
+        Ok(value) => value.parse().with_context(|| format!("invalid {}: {}", name, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Creates the cache with the limits set by `DID_CACHE_TTL_SECONDS` and `DID_CACHE_MAX_ENTRIES`.
+pub fn from_env() -> Result<DocumentCache> {
+    let ttl = Duration::from_secs(env_or("DID_CACHE_TTL_SECONDS", DEFAULT_TTL_SECONDS)?);
+    let max_entries = env_or("DID_CACHE_MAX_ENTRIES", DEFAULT_MAX_ENTRIES)?;
+    Ok(DocumentCache::new(ttl, max_entries))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurgeQuery {
+    // Purges only this DID; without it the whole cache is emptied
+    did: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeResponse {
+    purged: usize,
+}
+
+pub async fn cache_stats(State(cache): State<Arc<DocumentCache>>) -> Json<CacheStats> {
+    Json(cache.stats())
+}
+
+pub async fn purge_cache(
+    State(cache): State<Arc<DocumentCache>>,
+    Query(query): Query<PurgeQuery>,
+) -> Result<Json<PurgeResponse>, ApiError> {
+    let did = query
+        .did
+        .map(|did| CoreDID::parse(&did).map_err(|e| ApiError::InvalidInput(format!("invalid DID {}: {}", did, e))))
+        .transpose()?;
+    Ok(Json(PurgeResponse {
+        purged: cache.purge(did.as_ref()),
+    }))
+}
+
+pub(crate) fn router(cache: Arc<DocumentCache>) -> Router {
+    Router::new()
+        .route("/api/admin/did-cache", get(cache_stats).delete(purge_cache))
+        .with_state(cache)
+}
@@ -0,0 +1,282 @@
+//! DID resolution over HTTP, following the Universal Resolver driver interface, so other services and a
+//! Universal Resolver deployment can use this backend as their driver for the methods the verifier supports.
+
+use axum::{
+    extract::{Path, RawPathParams, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use identity_iota::{
+    did::{CoreDID, DID},
+    document::CoreDocument,
+};
+use identity_logic::{
+    resolution::{expand_did_jwk, expand_did_key, DidWebResolver},
+    verification::{Verifier, SUPPORTED_METHODS},
+    Error,
+};
+use serde::Serialize;
+use std::time::Instant;
+
+const RESOLUTION_CONTEXT: &str = "https://w3id.org/did-resolution/v1";
+const DID_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+const RESOLUTION_RESULT: &str = "application/ld+json;profile=\"https://w3id.org/did-resolution\"";
+const DID_LD_JSON: &str = "application/did+ld+json";
+const DID_JSON: &str = "application/did+json";
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_type: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_message: Option<String>,
+    // Milliseconds the resolution took
+    duration: u128,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deactivated: Option<bool>,
+}
+
+/// A DID resolution result as defined by the DID Resolution specification.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionResult {
+    #[serde(rename = "@context")]
+    context: &'static str,
+    did_document: Option<serde_json::Value>,
+    did_document_metadata: DocumentMetadata,
+    did_resolution_metadata: ResolutionMetadata,
+}
+
+/// How the caller asked for the result to be represented.
+enum Representation {
+    // The whole resolution result
+    Result,
+    // Only the DID document, in the given media type
+    Document(&'static str),
+}
+
+// Picks the first acceptable media type of the Accept header; None if none of them can be produced
+fn negotiate(headers: &HeaderMap) -> Option<Representation> {
+    let Some(accept) = headers.get(header::ACCEPT).and_then(|accept| accept.to_str().ok()) else {
+        return Some(Representation::Result);
+    };
+    accept.split(',').find_map(|media_type| {
+        let media_type = media_type.trim();
+        match media_type.split(';').next().unwrap_or_default().trim() {
+            "*/*" | "application/*" | "application/json" | "application/ld+json" => Some(Representation::Result),
+            DID_LD_JSON => Some(Representation::Document(DID_LD_JSON)),
+            DID_JSON => Some(Representation::Document(DID_JSON)),
+            _ => None,
+        }
+    })
+}
+
+// The W3C DID document, with the DID Core context for JSON-LD consumers
+fn document_json(doc: &CoreDocument) -> serde_json::Value {
+    let mut document = serde_json::to_value(doc).unwrap_or_default();
+    if let serde_json::Value::Object(fields) = &mut document {
+        fields.insert("@context".to_string(), serde_json::Value::String(DID_CONTEXT.to_string()));
+    }
+    document
+}
+
+fn failure(status: StatusCode, error: &'static str, message: impl Into<String>, started: Instant) -> Response {
+    let result = ResolutionResult {
+        context: RESOLUTION_CONTEXT,
+        did_document: None,
+        did_document_metadata: DocumentMetadata::default(),
+        did_resolution_metadata: ResolutionMetadata {
+            error: Some(error),
+            error_message: Some(message.into()),
+            duration: started.elapsed().as_millis(),
+            ..Default::default()
+        },
+    };
+    (status, [(header::CONTENT_TYPE, RESOLUTION_RESULT)], Json(result)).into_response()
+}
+
+// Resolves `did` with the handler of its method. Only did:iota needs the network, so the client is
+// connected for it alone, and only IOTA documents carry metadata
+async fn resolve(did: &CoreDID, web: &DidWebResolver) -> Result<(CoreDocument, DocumentMetadata), Error> {
+    match did.method() {
+        "key" => Ok((expand_did_key(did)?, DocumentMetadata::default())),
+        "jwk" => Ok((expand_did_jwk(did)?, DocumentMetadata::default())),
+        "web" => Ok((web.resolve(did).await?, DocumentMetadata::default())),
+        _ => {
+            // A malformed did:iota is rejected before the client connects
+            let doc = Verifier::new().resolve_iota(did).await?;
+            let metadata = DocumentMetadata {
+                created: doc.metadata.created.map(|created| created.to_rfc3339()),
+                updated: doc.metadata.updated.map(|updated| updated.to_rfc3339()),
+                deactivated: doc.metadata.deactivated.filter(|deactivated| *deactivated),
+            };
+            Ok((doc.core_document().clone(), metadata))
+        }
+    }
+}
+
+// The status and DID Resolution error a failed resolution is reported with. Network and node failures,
+// and hosts serving no valid document, are internal errors rather than missing DIDs
+fn failure_kind(error: &Error) -> (StatusCode, &'static str) {
+    match error {
+        Error::InvalidDid { .. } => (StatusCode::BAD_REQUEST, "invalidDid"),
+        Error::NotFound(_) => (StatusCode::NOT_FOUND, "notFound"),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, "internalError"),
+    }
+}
+
+// The DID as the client sent it. `Path` percent-decodes the segment, which would turn the port separator of
+// `did:web:localhost%3A8080` into a path separator, so the raw segment is used unless the client encoded the
+// whole DID
+fn requested_did(decoded: String, raw: Option<&str>) -> String {
+    match raw {
+        Some(raw) if raw.starts_with("did:") => raw.to_string(),
+        _ => decoded,
+    }
+}
+
+// Resolves a DID with the verifier's DID methods; deactivated DIDs are returned with status 410
+pub async fn resolve_identifier(
+    State(web): State<DidWebResolver>,
+    Path(decoded): Path<String>,
+    raw: RawPathParams,
+    headers: HeaderMap,
+) -> Response {
+    let did = requested_did(decoded, raw.iter().next().map(|(_, raw)| raw));
+    let started = Instant::now();
+    let Some(representation) = negotiate(&headers) else {
+        return failure(
+            StatusCode::NOT_ACCEPTABLE,
+            "representationNotSupported",
+            format!("supported media types are {}, {} and {}", RESOLUTION_RESULT, DID_LD_JSON, DID_JSON),
+            started,
+        );
+    };
+
+    // 1. Parse the DID and check this driver handles its method
+    let did = match CoreDID::parse(&did) {
+        Ok(did) => did,
+        Err(e) => return failure(StatusCode::BAD_REQUEST, "invalidDid", format!("{}: {}", did, e), started),
+    };
+    if !SUPPORTED_METHODS.contains(&did.method()) {
+        return failure(
+            StatusCode::NOT_IMPLEMENTED,
+            "methodNotSupported",
+            format!("did:{} is not supported", did.method()),
+            started,
+        );
+    }
+
+    // 2. Resolve it
+    let (doc, metadata) = match resolve(&did, &web).await {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            let (status, error) = failure_kind(&e);
+            return failure(status, error, e.to_string(), started);
+        }
+    };
+
+    // 3. Represent the document as requested
+    let deactivated = metadata.deactivated.unwrap_or(false);
+    let status = if deactivated { StatusCode::GONE } else { StatusCode::OK };
+    match representation {
+        Representation::Document(content_type) => {
+            (status, [(header::CONTENT_TYPE, content_type)], Json(document_json(&doc))).into_response()
+        }
+        Representation::Result => {
+            let result = ResolutionResult {
+                context: RESOLUTION_CONTEXT,
+                did_document: Some(document_json(&doc)),
+                did_document_metadata: metadata,
+                did_resolution_metadata: ResolutionMetadata {
+                    content_type: Some(DID_LD_JSON),
+                    duration: started.elapsed().as_millis(),
+                    ..Default::default()
+                },
+            };
+            (status, [(header::CONTENT_TYPE, RESOLUTION_RESULT)], Json(result)).into_response()
+        }
+    }
+}
+
+pub(crate) fn router() -> Router {
+    Router::new()
+        .route("/1.0/identifiers/:did", get(resolve_identifier))
+        .with_state(DidWebResolver::from_env())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::{to_bytes, Body},
+        http::Request,
+    };
+    use tower::ServiceExt;
+
+    async fn get(path: &str) -> (StatusCode, serde_json::Value) {
+        let response = router().oneshot(Request::get(path).body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn error(result: &serde_json::Value) -> &str {
+        result["didResolutionMetadata"]["error"].as_str().unwrap_or_default()
+    }
+
+    #[test]
+    fn keeps_the_port_separator_of_a_did_web() {
+        let did = requested_did("did:web:localhost:8080".to_string(), Some("did:web:localhost%3A8080"));
+        assert_eq!(did, "did:web:localhost%3A8080");
+
+        // A client that encoded the whole DID also encoded its percent sign
+        let did = requested_did("did:web:localhost%3A8080".to_string(), Some("did%3Aweb%3Alocalhost%253A8080"));
+        assert_eq!(did, "did:web:localhost%3A8080");
+    }
+
+    #[tokio::test]
+    async fn fetches_a_did_web_with_a_port_from_that_port() {
+        // Nothing listens on port 1, so the lookup fails and names the URL it tried
+        let (status, result) = get("/1.0/identifiers/did:web:localhost%3A1").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(error(&result), "internalError");
+        let message = result["didResolutionMetadata"]["errorMessage"].as_str().unwrap();
+        assert!(message.contains("https://localhost:1/.well-known/did.json"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn resolves_a_did_key_without_the_network() {
+        let did = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+        let (status, result) = get(&format!("/1.0/identifiers/{}", did)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(result["didDocument"]["id"], did);
+        assert_eq!(result["didDocument"]["@context"], DID_CONTEXT);
+    }
+
+    #[tokio::test]
+    async fn reports_malformed_and_unsupported_dids() {
+        let (status, result) = get("/1.0/identifiers/not-a-did").await;
+        assert_eq!((status, error(&result)), (StatusCode::BAD_REQUEST, "invalidDid"));
+
+        let (status, result) = get("/1.0/identifiers/did:iota:not-an-object-id").await;
+        assert_eq!((status, error(&result)), (StatusCode::BAD_REQUEST, "invalidDid"));
+
+        let (status, result) = get("/1.0/identifiers/did:example:123").await;
+        assert_eq!((status, error(&result)), (StatusCode::NOT_IMPLEMENTED, "methodNotSupported"));
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::{anyhow, bail, Context, Result};
+use axum::extract::{Json, State};
+use identity_eddsa_verifier::EdDSAJwsVerifier;
+use identity_iota::{
+    core::{Duration, FromJson, Object, OrderedSet, Timestamp, Url},
+    credential::{
+        Credential, DomainLinkageConfiguration, DomainLinkageCredentialBuilder, Jwt,
+        JwtCredentialValidationOptions, JwtDomainLinkageValidator, LinkedDomainService,
+    },
+    did::DID,
+    document::CoreDocument,
+    iota::{IotaDID, IotaDocument},
+};
+use identity_logic::{
+    cache::DocumentCache,
+    get_controller_client,
+    issuance::sign_credential,
+    publish_did_update,
+    store::{create_or_load_did, load_controller_key, open_vault, save_did_document, VaultSession},
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, sync::Arc};
+
+use crate::{error::ApiError, AppState};
+
+/// Origin the university's issuer DID is linked to when `ISSUER_ORIGIN` is not set.
+const DEFAULT_ISSUER_ORIGIN: &str = "https://example.edu";
+
+/// Where the signed DID configuration is kept, so it is only signed again once it expires.
+const CONFIGURATION_FILE: &str = "./did_configuration.json";
+
+/// Fragment of the `LinkedDomains` service published in the issuer DID document.
+const LINKED_DOMAIN_FRAGMENT: &str = "linked-domain";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedOrigin {
+    pub issuer: String,
+    pub origin: String,
+}
+
+/// Returns the web origin the issuer DID is published under.
+pub fn issuer_origin() -> Result<Url> {
+    let origin = std::env::var("ISSUER_ORIGIN").unwrap_or_else(|_| DEFAULT_ISSUER_ORIGIN.to_string());
+    Url::parse(&origin).with_context(|| format!("invalid ISSUER_ORIGIN: {}", origin))
+}
+
+// A DID configuration together with the issuer, origin and expiry it was signed for
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedConfiguration {
+    issuer: String,
+    origin: String,
+    expires: Timestamp,
+    configuration: DomainLinkageConfiguration,
+}
+
+impl SignedConfiguration {
+    // The stored configuration, if it was signed by `issuer` for `origin` and has not expired
+    fn load(issuer: &IotaDID, origin: &Url) -> Option<DomainLinkageConfiguration> {
+        let json = fs::read_to_string(CONFIGURATION_FILE).ok()?;
+        let stored: SignedConfiguration = serde_json::from_str(&json).ok()?;
+        let current = stored.issuer == issuer.as_str() && stored.origin == origin.as_str() && stored.expires > Timestamp::now_utc();
+        current.then_some(stored.configuration)
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::write(CONFIGURATION_FILE, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write DID configuration {}", CONFIGURATION_FILE))
+    }
+}
+
+// Whether the document declares `origin` in one of its LinkedDomains services
+fn declares_origin(document: &CoreDocument, origin: &Url) -> bool {
+    document
+        .service()
+        .iter()
+        .cloned()
+        .filter_map(|service| LinkedDomainService::try_from(service).ok())
+        .any(|service| service.domains().contains(origin))
+}
+
+/// Makes sure the issuer's published DID document declares `origin` in its `LinkedDomains` service,
+/// replacing a service that declares another origin, so verifiers can check the DID-to-domain direction.
+/// A published update drops the document from `did_cache`. Returns the current document.
+async fn publish_linked_domain(
+    vault: &VaultSession,
+    did_cache: &DocumentCache,
+    doc_file: &str,
+    issuer_doc: &IotaDocument,
+    origin: &Url,
+) -> Result<IotaDocument, ApiError> {
+    if declares_origin(issuer_doc.core_document(), origin) {
+        return Ok(issuer_doc.clone());
+    }
+    let controller = load_controller_key(doc_file)?;
+    let client = get_controller_client(&vault.storage, &controller).await?;
+
+    // Start from the latest published version rather than the local copy
+    let mut document = client.resolve_did(issuer_doc.id()).await.map_err(ApiError::identity)?;
+    if declares_origin(document.core_document(), origin) {
+        save_did_document(doc_file, &document)?;
+        return Ok(document);
+    }
+
+    let service_url = document
+        .id()
+        .to_url()
+        .join(format!("#{}", LINKED_DOMAIN_FRAGMENT))
+        .map_err(ApiError::identity)?;
+    document.remove_service(&service_url);
+    let domains: OrderedSet<Url> = [origin.clone()].into_iter().collect();
+    let service = LinkedDomainService::new(service_url, domains, Object::new()).map_err(ApiError::identity)?;
+    document.insert_service(service.into()).map_err(ApiError::identity)?;
+
+    let published = publish_did_update(&client, document).await?;
+    did_cache.purge(Some(published.id().as_ref()));
+    save_did_document(doc_file, &published)?;
+    println!(">> Published linked domain {}", origin);
+    Ok(published)
+}
+
+// Serves `/.well-known/did-configuration.json` with a Domain Linkage Credential for the issuer DID.
+// The credential is signed once and served from `CONFIGURATION_FILE` until it expires.
+pub async fn did_configuration(State(state): State<Arc<AppState>>) -> Result<Json<DomainLinkageConfiguration>, ApiError> {
+    let issuer_doc_file = "./issuer_doc.json";
+    let issuer_stronghold_path = "./issuer.stronghold";
+
+    // 1. Create/Load Issuer DID
+    let (issuer_doc, issuer_fragment) = create_or_load_did(
+        issuer_doc_file,
+        "./issuer_fragment.txt",
+        issuer_stronghold_path,
+    ).await?;
+    let origin = issuer_origin().map_err(ApiError::Configuration)?;
+
+    // 2. Serve the configuration signed earlier; check again once we hold the vault, so it is signed only once
+    if let Some(configuration) = SignedConfiguration::load(issuer_doc.id(), &origin) {
+        return Ok(Json(configuration));
+    }
+    let issuer_vault = open_vault(issuer_stronghold_path).await?;
+    if let Some(configuration) = SignedConfiguration::load(issuer_doc.id(), &origin) {
+        return Ok(Json(configuration));
+    }
+
+    // 3. Declare the origin in the issuer DID document
+    let issuer_doc = publish_linked_domain(&issuer_vault, &state.did_cache, issuer_doc_file, &issuer_doc, &origin).await?;
+
+    // 4. Build the Domain Linkage Credential binding the DID to our origin
+    let expires = Timestamp::now_utc()
+        .checked_add(Duration::weeks(10))
+        .ok_or_else(|| ApiError::identity(anyhow!("domain linkage credential expiry is out of range")))?;
+    let domain_linkage_credential: Credential = DomainLinkageCredentialBuilder::new()
+        .issuer(issuer_doc.id().clone().into())
+        .origin(origin.clone())
+        .issuance_date(Timestamp::now_utc())
+        .expiration_date(expires)
+        .build()
+        .map_err(ApiError::identity)?;
+
+    // 5. Sign it with the issuer key and keep it for later requests
+    let jwt: Jwt = sign_credential(&issuer_doc, &issuer_vault.storage, &issuer_fragment, &domain_linkage_credential).await?;
+    let signed = SignedConfiguration {
+        issuer: issuer_doc.id().to_string(),
+        origin: origin.to_string(),
+        expires,
+        configuration: DomainLinkageConfiguration::new(vec![jwt]),
+    };
+    signed.save().map_err(ApiError::storage)?;
+
+    Ok(Json(signed.configuration))
+}
+
+/// Loads the DID configuration for `origin`, either from the local fixture named by
+/// `DID_CONFIGURATION_FIXTURE` or from the origin's `/.well-known/did-configuration.json`.
+async fn load_configuration(origin: &Url) -> Result<DomainLinkageConfiguration> {
+    if let Ok(fixture) = std::env::var("DID_CONFIGURATION_FIXTURE") {
+        let json = fs::read_to_string(&fixture)
+            .with_context(|| format!("failed to read DID configuration fixture {}", fixture))?;
+        return DomainLinkageConfiguration::from_json(&json).context("invalid DID configuration fixture");
+    }
+
+    DomainLinkageConfiguration::fetch_configuration(origin.clone())
+        .await
+        .with_context(|| format!("failed to fetch DID configuration from {}", origin))
+}
+
+/// Validates that `issuer_doc` is linked to one of the domains its `LinkedDomains` services declare
+/// and returns the verified origin. With `expected_origin`, only that origin is checked, and it must
+/// be one the DID document declares.
+pub async fn verify_domain_linkage(issuer_doc: &CoreDocument, expected_origin: Option<&Url>) -> Result<Url> {
+    let mut origins: Vec<Url> = issuer_doc
+        .service()
+        .iter()
+        .cloned()
+        .filter_map(|service| LinkedDomainService::try_from(service).ok())
+        .flat_map(|service| service.domains().to_vec())
+        .collect();
+
+    if origins.is_empty() {
+        bail!("issuer {} does not declare a linked domain", issuer_doc.id());
+    }
+
+    if let Some(origin) = expected_origin {
+        if !origins.contains(origin) {
+            bail!("issuer {} does not declare {} as a linked domain", issuer_doc.id(), origin);
+        }
+        origins = vec![origin.clone()];
+    }
+
+    let validator = JwtDomainLinkageValidator::with_signature_verifier(EdDSAJwsVerifier::default());
+    let mut last_error = anyhow!("no linked domain could be verified");
+
+    for origin in origins {
+        let configuration = match load_configuration(&origin).await {
+            Ok(configuration) => configuration,
+            Err(e) => {
+                last_error = e;
+                continue;
+            }
+        };
+
+        match validator.validate_linkage(issuer_doc, &configuration, &origin, &JwtCredentialValidationOptions::default()) {
+            Ok(()) => return Ok(origin),
+            Err(e) => last_error = anyhow!("domain linkage to {} is invalid: {}", origin, e),
+        }
+    }
+
+    Err(last_error)
+}
@@ -0,0 +1,149 @@
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+/// Errors returned by the API handlers, rendered as RFC 7807 problem details.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("identity operation failed")]
+    Identity(#[source] anyhow::Error),
+    #[error("storage operation failed")]
+    Storage(#[source] anyhow::Error),
+    #[error("IOTA network unavailable")]
+    Network(#[source] anyhow::Error),
+    #[error("DID resolution failed")]
+    Resolution(#[source] anyhow::Error),
+    #[error("server misconfigured")]
+    Configuration(#[source] anyhow::Error),
+}
+
+impl From<identity_logic::Error> for ApiError {
+    fn from(error: identity_logic::Error) -> Self {
+        use identity_logic::Error;
+
+        match error {
+            Error::NetworkUnavailable { .. } | Error::ClientCreation(_) | Error::FaucetFailed(_) => {
+                ApiError::Network(error.into())
+            }
+            Error::MissingPackageId | Error::InvalidPackageId(_) => ApiError::Configuration(error.into()),
+            Error::VaultLocked(_) | Error::Storage(_) => ApiError::Storage(error.into()),
+            Error::KeyGeneration(_) | Error::PublishFailed(_) | Error::Signing(_) | Error::InvalidDocument(_) => {
+                ApiError::Identity(error.into())
+            }
+            Error::InvalidSubject(_)
+            | Error::InvalidCredential(_)
+            | Error::InvalidPresentation(_)
+            | Error::InvalidName(_)
+            | Error::InvalidDid { .. } => ApiError::InvalidInput(error.to_string()),
+            Error::NotFound(message) => ApiError::NotFound(message),
+            Error::Resolution { .. } => ApiError::Resolution(error.into()),
+        }
+    }
+}
+
+impl ApiError {
+    pub fn identity(error: impl Into<anyhow::Error>) -> Self {
+        ApiError::Identity(error.into())
+    }
+
+    pub fn storage(error: impl Into<anyhow::Error>) -> Self {
+        ApiError::Storage(error.into())
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Identity(_) | ApiError::Storage(_) | ApiError::Configuration(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            ApiError::Network(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Resolution(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    /// Stable identifier clients can branch on; also used as the problem `type`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::InvalidInput(_) => "invalid-input",
+            ApiError::NotFound(_) => "not-found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Identity(_) => "identity-error",
+            ApiError::Storage(_) => "storage-error",
+            ApiError::Network(_) => "network-unavailable",
+            ApiError::Resolution(_) => "resolution-failed",
+            ApiError::Configuration(_) => "configuration-error",
+        }
+    }
+
+    // The underlying error of a server-side failure; never sent to clients
+    fn source_chain(&self) -> Option<&anyhow::Error> {
+        match self {
+            ApiError::Identity(source)
+            | ApiError::Storage(source)
+            | ApiError::Network(source)
+            | ApiError::Resolution(source)
+            | ApiError::Configuration(source) => Some(source),
+            ApiError::InvalidInput(_) | ApiError::NotFound(_) | ApiError::Conflict(_) | ApiError::Unauthorized(_) => None,
+        }
+    }
+
+    /// Writes server-side failures with their full source chain to the server log; client errors are
+    /// only reported to the client.
+    pub fn log(&self) {
+        if self.status().is_server_error() {
+            match self.source_chain() {
+                Some(source) => eprintln!("Error handling request: {}: {:#}", self, source),
+                None => eprintln!("Error handling request: {}", self),
+            }
+        }
+    }
+
+    /// Explains client errors; server errors only get a generic description, so internal paths,
+    /// vault and node messages stay in the server log.
+    pub fn detail(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProblemDetails {
+    #[serde(rename = "type")]
+    type_: String,
+    title: String,
+    status: u16,
+    detail: String,
+    code: &'static str,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        self.log();
+
+        let problem = ProblemDetails {
+            type_: format!("/problems/{}", self.code()),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail: self.detail(),
+            code: self.code(),
+        };
+
+        (status, [(header::CONTENT_TYPE, "application/problem+json")], Json(problem)).into_response()
+    }
+}
@@ -0,0 +1,434 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use identity_iota::{
+    core::{Object, Timestamp, Url},
+    did::{DIDUrl, DID},
+    document::Service,
+    iota::IotaDocument,
+};
+use identity_logic::{
+    deactivate_did, get_controller_client, publish_did_update,
+    store::{create_or_load_did_with_progress, load_controller_key, load_existing_did, open_vault, save_did_document},
+    PublishStage,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::{mpsc, watch};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{
+    error::ApiError,
+    revocation::{publish_revocations, revocation_service_url},
+    AppState,
+};
+
+/// How long a finished job can still be polled before it is forgotten.
+const FINISHED_JOB_TTL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobKind {
+    CreateDid,
+    UpdateDid,
+    DeactivateDid,
+    PublishRevocation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStage {
+    Queued,
+    Funding,
+    Signing,
+    Submitted,
+    Confirmed,
+    Failed,
+}
+
+impl JobStage {
+    fn is_terminal(self) -> bool {
+        matches!(self, JobStage::Confirmed | JobStage::Failed)
+    }
+}
+
+impl From<PublishStage> for JobStage {
+    fn from(stage: PublishStage) -> Self {
+        match stage {
+            PublishStage::Funding => JobStage::Funding,
+            PublishStage::Signing => JobStage::Signing,
+            PublishStage::Submitted => JobStage::Submitted,
+            PublishStage::Confirmed => JobStage::Confirmed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatus {
+    id: String,
+    kind: JobKind,
+    stage: JobStage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    updated_at: String,
+    // When the job was confirmed or failed; it is pruned FINISHED_JOB_TTL later
+    #[serde(skip)]
+    finished_at: Option<Instant>,
+}
+
+/// Slow on-chain operations running in the background, each observable through a watch channel.
+#[derive(Default)]
+pub struct JobQueue {
+    jobs: Mutex<HashMap<String, Arc<watch::Sender<JobStatus>>>>,
+}
+
+impl JobQueue {
+    fn create(&self, kind: JobKind) -> JobHandle {
+        let id = nanoid::nanoid!();
+        let (sender, _) = watch::channel(JobStatus {
+            id: id.clone(),
+            kind,
+            stage: JobStage::Queued,
+            result: None,
+            error: None,
+            updated_at: Timestamp::now_utc().to_rfc3339(),
+            finished_at: None,
+        });
+        let sender = Arc::new(sender);
+        let mut jobs = self.jobs.lock().expect("job map poisoned");
+        // Forget jobs that finished long enough ago, so the map only grows with running jobs
+        jobs.retain(|_, sender| {
+            sender
+                .borrow()
+                .finished_at
+                .map_or(true, |finished_at| finished_at.elapsed() < FINISHED_JOB_TTL)
+        });
+        jobs.insert(id, sender.clone());
+        JobHandle { sender }
+    }
+
+    /// Runs `task` in the background as a job of `kind` and returns the job ID. The job is confirmed with
+    /// the task's result or failed with its error.
+    fn spawn<F, Fut>(&self, kind: JobKind, task: F) -> String
+    where
+        F: FnOnce(JobHandle) -> Fut,
+        Fut: Future<Output = Result<serde_json::Value, ApiError>> + Send + 'static,
+    {
+        let job = self.create(kind);
+        let job_id = job.id();
+        let task = task(job.clone());
+        tokio::spawn(async move {
+            match task.await {
+                Ok(result) => job.complete(result),
+                Err(e) => job.fail(&e),
+            }
+        });
+        job_id
+    }
+
+    fn subscribe(&self, id: &str) -> Option<watch::Receiver<JobStatus>> {
+        self.jobs
+            .lock()
+            .expect("job map poisoned")
+            .get(id)
+            .map(|sender| sender.subscribe())
+    }
+}
+
+/// Write side of a job, owned by the task running it.
+#[derive(Clone)]
+struct JobHandle {
+    sender: Arc<watch::Sender<JobStatus>>,
+}
+
+impl JobHandle {
+    fn id(&self) -> String {
+        self.sender.borrow().id.clone()
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut JobStatus)) {
+        self.sender.send_modify(|status| {
+            apply(status);
+            status.updated_at = Timestamp::now_utc().to_rfc3339();
+            if status.stage.is_terminal() {
+                status.finished_at.get_or_insert_with(Instant::now);
+            }
+        });
+    }
+
+    fn stage(&self, stage: JobStage) {
+        self.update(|status| status.stage = stage);
+    }
+
+    fn complete(&self, result: serde_json::Value) {
+        self.update(|status| {
+            status.stage = JobStage::Confirmed;
+            status.result = Some(result);
+        });
+    }
+
+    fn fail(&self, error: &ApiError) {
+        let message = error.detail();
+        error.log();
+        self.update(|status| {
+            status.stage = JobStage::Failed;
+            status.error = Some(message);
+        });
+    }
+}
+
+// --- API Handlers ---
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateDidJob {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceEntry {
+    // Fragment of the service ID, e.g. "website"
+    fragment: String,
+    #[serde(rename = "type")]
+    type_: String,
+    endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDidJob {
+    role: String,
+    add_service: Option<ServiceEntry>,
+    // Fragment of the service to remove
+    remove_service: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeactivateDidJob {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishRevocationJob {
+    role: String,
+    // Fragment of the RevocationBitmap2022 service, created if the DID document lacks it
+    service: String,
+    // Indices of the credentials to revoke in the same update
+    #[serde(default)]
+    revoke_indices: Vec<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobAccepted {
+    job_id: String,
+}
+
+// Files backing each role's identity, as used by the synchronous handlers
+fn identity_files(role: &str) -> Option<(&'static str, &'static str, &'static str)> {
+    match role {
+        "holder" => Some(("./holder_doc.json", "./holder_fragment.txt", "./holder.stronghold")),
+        "issuer" => Some(("./issuer_doc.json", "./issuer_fragment.txt", "./issuer.stronghold")),
+        _ => None,
+    }
+}
+
+fn role_files(role: &str) -> Result<(&'static str, &'static str, &'static str), ApiError> {
+    identity_files(role).ok_or_else(|| ApiError::InvalidInput(format!("unknown role {}", role)))
+}
+
+fn service_url(doc: &IotaDocument, fragment: &str) -> Result<DIDUrl, ApiError> {
+    doc.id()
+        .to_url()
+        .join(format!("#{}", fragment.trim_start_matches('#')))
+        .map_err(|e| ApiError::InvalidInput(format!("invalid service fragment {}: {}", fragment, e)))
+}
+
+fn accepted(job_id: String) -> (StatusCode, Json<JobAccepted>) {
+    (StatusCode::ACCEPTED, Json(JobAccepted { job_id }))
+}
+
+// Starts creating (or loading) a role's DID in the background and returns the job ID immediately
+pub async fn start_create_did(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<CreateDidJob>,
+) -> Result<(StatusCode, Json<JobAccepted>), ApiError> {
+    let (doc_file, fragment_file, stronghold_path) = role_files(&body.role)?;
+
+    let job_id = state.jobs.spawn(JobKind::CreateDid, |job| async move {
+        // Confirmation is reported by complete() once the DID has also been saved
+        let progress = |stage: PublishStage| {
+            if stage != PublishStage::Confirmed {
+                job.stage(stage.into());
+            }
+        };
+        let (doc, _) = create_or_load_did_with_progress(doc_file, fragment_file, stronghold_path, &progress).await?;
+        Ok::<_, ApiError>(serde_json::json!({ "did": doc.id().to_string() }))
+    });
+
+    Ok(accepted(job_id))
+}
+
+// Publishes a new version of a role's DID document, adding and/or removing a service
+pub async fn start_update_did(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<UpdateDidJob>,
+) -> Result<(StatusCode, Json<JobAccepted>), ApiError> {
+    let (doc_file, _, stronghold_path) = role_files(&body.role)?;
+    let stored = load_existing_did(doc_file, &body.role)?;
+    if body.add_service.is_none() && body.remove_service.is_none() {
+        return Err(ApiError::InvalidInput("nothing to update: pass addService or removeService".to_string()));
+    }
+    let add_service = match body.add_service {
+        Some(entry) => {
+            let endpoint = Url::parse(&entry.endpoint)
+                .map_err(|e| ApiError::InvalidInput(format!("invalid service endpoint {}: {}", entry.endpoint, e)))?;
+            let service = Service::builder(Object::new())
+                .id(service_url(&stored, &entry.fragment)?)
+                .type_(entry.type_)
+                .service_endpoint(endpoint)
+                .build()
+                .map_err(|e| ApiError::InvalidInput(format!("invalid service: {}", e)))?;
+            Some(service)
+        }
+        None => None,
+    };
+    let remove_service = body
+        .remove_service
+        .map(|fragment| service_url(&stored, &fragment))
+        .transpose()?;
+
+    let did_cache = state.did_cache.clone();
+    let job_id = state.jobs.spawn(JobKind::UpdateDid, |job| async move {
+        // 1. Fund the controller, then apply the changes to the latest published version
+        job.stage(JobStage::Funding);
+        let vault = open_vault(stronghold_path).await?;
+        let client = get_controller_client(&vault.storage, &load_controller_key(doc_file)?).await?;
+
+        job.stage(JobStage::Signing);
+        let mut document = client.resolve_did(stored.id()).await.map_err(ApiError::identity)?;
+        if let Some(service_url) = &remove_service {
+            document
+                .remove_service(service_url)
+                .ok_or_else(|| ApiError::NotFound(format!("the DID document has no service {}", service_url)))?;
+        }
+        if let Some(service) = add_service {
+            document.insert_service(service).map_err(ApiError::identity)?;
+        }
+
+        // 2. Publish it, keep the local copy in sync and stop verifiers from using the previous version
+        job.stage(JobStage::Submitted);
+        let published = publish_did_update(&client, document).await?;
+        did_cache.purge(Some(published.id().as_ref()));
+        save_did_document(doc_file, &published)?;
+        Ok::<_, ApiError>(serde_json::json!({ "did": published.id().to_string() }))
+    });
+
+    Ok(accepted(job_id))
+}
+
+// Deactivates a role's DID; it keeps resolving, marked as deactivated
+pub async fn start_deactivate_did(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<DeactivateDidJob>,
+) -> Result<(StatusCode, Json<JobAccepted>), ApiError> {
+    let (doc_file, _, stronghold_path) = role_files(&body.role)?;
+    let stored = load_existing_did(doc_file, &body.role)?;
+
+    let did_cache = state.did_cache.clone();
+    let job_id = state.jobs.spawn(JobKind::DeactivateDid, |job| async move {
+        job.stage(JobStage::Funding);
+        let vault = open_vault(stronghold_path).await?;
+        let client = get_controller_client(&vault.storage, &load_controller_key(doc_file)?).await?;
+
+        job.stage(JobStage::Submitted);
+        deactivate_did(&client, stored.id()).await?;
+        // A cached copy would keep the deactivated DID verifying until it expired
+        did_cache.purge(Some(stored.id().as_ref()));
+        let deactivated = client.resolve_did(stored.id()).await.map_err(ApiError::identity)?;
+        save_did_document(doc_file, &deactivated)?;
+        Ok::<_, ApiError>(serde_json::json!({
+            "did": deactivated.id().to_string(),
+            "deactivated": deactivated.metadata.deactivated.unwrap_or(false),
+        }))
+    });
+
+    Ok(accepted(job_id))
+}
+
+// Publishes a role's revocation service, revoking the given credential indices in the same update
+pub async fn start_publish_revocation(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<PublishRevocationJob>,
+) -> Result<(StatusCode, Json<JobAccepted>), ApiError> {
+    let (doc_file, _, stronghold_path) = role_files(&body.role)?;
+    let stored = load_existing_did(doc_file, &body.role)?;
+    let service_url = revocation_service_url(&stored, &body.service)?;
+
+    let did_cache = state.did_cache.clone();
+    let job_id = state.jobs.spawn(JobKind::PublishRevocation, |job| async move {
+        job.stage(JobStage::Funding);
+        let vault = open_vault(stronghold_path).await?;
+        let published = publish_revocations(&vault, &did_cache, doc_file, &stored, &service_url, &body.revoke_indices).await?;
+        Ok::<_, ApiError>(serde_json::json!({
+            "service": service_url.to_string(),
+            "published": published,
+            "revoked": body.revoke_indices,
+        }))
+    });
+
+    Ok(accepted(job_id))
+}
+
+pub async fn job_status(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<JobStatus>, ApiError> {
+    let receiver = state
+        .jobs
+        .subscribe(&id)
+        .ok_or_else(|| ApiError::NotFound(format!("job {} not found", id)))?;
+    let status = receiver.borrow().clone();
+    Ok(Json(status))
+}
+
+// Streams every stage change of a job as Server-Sent Events, ending after it is confirmed or failed
+pub async fn job_events(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Sse<ReceiverStream<Result<Event, axum::Error>>>, ApiError> {
+    let mut receiver = state
+        .jobs
+        .subscribe(&id)
+        .ok_or_else(|| ApiError::NotFound(format!("job {} not found", id)))?;
+
+    let (tx, rx) = mpsc::channel(8);
+    tokio::spawn(async move {
+        loop {
+            let status = receiver.borrow_and_update().clone();
+            let terminal = status.stage.is_terminal();
+            let event = Event::default().event("stage").json_data(&status);
+            if tx.send(event).await.is_err() || terminal {
+                break;
+            }
+            if receiver.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,162 @@
+//! The workshop scenarios, each a thin set of routes over the shared issuance, presentation and
+//! verification steps in `identity_logic`. `main` serves them together, `bin/supply-chain` on its own.
+//! The developer tools under `/api/tools`, DID resolution under `/1.0/identifiers` and the trusted issuer and
+//! DID cache admin APIs under `/api/admin` are served with any of them.
+
+mod admin;
+mod batch;
+mod degree;
+mod did_cache;
+mod did_resolution;
+mod domain_linkage;
+mod error;
+mod jobs;
+mod pipeline;
+mod policy;
+mod report;
+mod revocation;
+mod scenarios;
+mod schema;
+mod supply_chain;
+mod templates;
+mod tools;
+mod trust_registry;
+mod wallet;
+
+use admin::AdminGuard;
+use anyhow::{bail, Result};
+use axum::Router;
+use identity_logic::cache::DocumentCache;
+use jobs::JobQueue;
+use policy::VerifierPolicy;
+use revocation::RevocationIndices;
+use scenarios::ScenarioRegistry;
+use schema::SchemaRegistry;
+use std::sync::Arc;
+use templates::TemplateRegistry;
+use tokio::sync::RwLock;
+use trust_registry::TrustRegistry;
+use wallet::CredentialWallet;
+
+/// A use case served under its own routes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scenario {
+    /// University degrees: `/api/holder`, `/api/issuer`, `/api/verifier` and their supporting APIs.
+    Degree,
+    /// Product origin and chain of custody: `/api/supply-chain`.
+    SupplyChain,
+    /// Use cases described in `scenarios/`: `/api/scenarios`.
+    Configured,
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 3] = [Scenario::Degree, Scenario::SupplyChain, Scenario::Configured];
+
+    /// Parses a comma-separated list such as `degree,supply-chain`.
+    pub fn parse_list(list: &str) -> Result<Vec<Scenario>> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| match name {
+                "degree" => Ok(Scenario::Degree),
+                "supply-chain" => Ok(Scenario::SupplyChain),
+                "configured" => Ok(Scenario::Configured),
+                other => bail!("unknown scenario {} (expected degree, supply-chain or configured)", other),
+            })
+            .collect()
+    }
+}
+
+pub(crate) struct AppState {
+    // We only need the package ID in the state to allow the client to be created in the handlers.
+    package_id: String,
+    // Issuers the verifier accepts, per credential type; shared with the supply-chain retailer
+    trust_registry: Arc<RwLock<TrustRegistry>>,
+    // Background on-chain operations, polled or streamed by job ID
+    jobs: JobQueue,
+    // Credentials the holder has received
+    wallet: RwLock<CredentialWallet>,
+    // Per credential type verification settings
+    verifier_policy: VerifierPolicy,
+    // JSON Schemas for credential subjects, per credential type
+    schemas: SchemaRegistry,
+    // Credential types the issuer can issue through the generic template endpoint
+    templates: TemplateRegistry,
+    // Next free revocation bitmap index per issuer revocation service
+    revocation_indices: RwLock<RevocationIndices>,
+    // Use cases described in `scenarios/`, with the wallets of their holder roles
+    scenarios: ScenarioRegistry,
+    // DID documents resolved by earlier verifications
+    did_cache: Arc<DocumentCache>,
+}
+
+impl AppState {
+    /// Loads the registries and stores of the degree and configured scenarios from the working directory.
+    fn load(
+        package_id: String,
+        trust_registry: Arc<RwLock<TrustRegistry>>,
+        did_cache: Arc<DocumentCache>,
+    ) -> Result<Self> {
+        let templates = TemplateRegistry::load("./templates")?;
+        Ok(AppState {
+            package_id,
+            trust_registry,
+            jobs: JobQueue::default(),
+            wallet: RwLock::new(CredentialWallet::load("./holder_credentials.json")?),
+            verifier_policy: VerifierPolicy::load("./verifier_policy.json")?,
+            schemas: SchemaRegistry::load("./schemas")?,
+            scenarios: ScenarioRegistry::load("./scenarios", &templates)?,
+            templates,
+            revocation_indices: RwLock::new(RevocationIndices::load("./revocation_indices.json")?),
+            did_cache,
+        })
+    }
+}
+
+/// Builds the API serving `scenarios`. `IOTA_IDENTITY_PKG_ID` must be set.
+pub fn app(scenarios: &[Scenario]) -> Result<Router> {
+    // The environment variable must be set before starting the server
+    let package_id = std::env::var("IOTA_IDENTITY_PKG_ID")
+        .map_err(|_| anyhow::anyhow!("The IOTA_IDENTITY_PKG_ID environment variable must be set."))?;
+
+    // The degree and configured scenarios share one state, so they never hand out the same revocation index
+    let mut state: Option<Arc<AppState>> = None;
+    // Admin routes of every scenario require the same token
+    let admin = AdminGuard::from_env();
+    // Every scenario's verifier shares one DID document cache
+    let did_cache = Arc::new(did_cache::from_env()?);
+    // Issuers trusted through the admin API are trusted by every scenario's verifier
+    let trust_registry = Arc::new(RwLock::new(TrustRegistry::load("./trust_registry.json")?));
+    let mut app = tools::router()
+        .merge(did_resolution::router())
+        .merge(admin.protect(did_cache::router(did_cache.clone())))
+        .merge(admin.protect(trust_registry::router(trust_registry.clone())));
+    for scenario in scenarios {
+        app = app.merge(match scenario {
+            Scenario::SupplyChain => {
+                let state = supply_chain::SupplyChainState::load(trust_registry.clone(), did_cache.clone())?;
+                supply_chain::router(Arc::new(state))
+            }
+            Scenario::Degree | Scenario::Configured => {
+                let state = match state.clone() {
+                    Some(state) => state,
+                    None => {
+                        let app_state = AppState::load(package_id.clone(), trust_registry.clone(), did_cache.clone())?;
+                        state.insert(Arc::new(app_state)).clone()
+                    }
+                };
+                match scenario {
+                    Scenario::Degree => degree::router(state, &admin),
+                    _ => scenarios::router(state),
+                }
+            }
+        });
+    }
+
+    let cors_layer = tower_http::cors::CorsLayer::new()
+        .allow_origin(tower_http::cors::Any)
+        .allow_methods([axum::http::Method::GET, axum::http::Method::POST, axum::http::Method::DELETE])
+        .allow_headers([axum::http::header::CONTENT_TYPE, axum::http::header::AUTHORIZATION]);
+
+    Ok(app.layer(cors_layer))
+}
@@ -0,0 +1,295 @@
+//! The presentation checks shared by the verifiers of every scenario. A scenario describes what it
+//! accepts through [`VerifierRules`], and [`verify_presentation`] records each check in a [`VerificationReport`].
+
+use axum::http::StatusCode;
+use identity_iota::{
+    core::{Object, Url},
+    credential::{DecodedJwtCredential, DecodedJwtPresentation, FailFast, Jwt},
+    did::CoreDID,
+    document::CoreDocument,
+};
+use identity_logic::{
+    cache::DocumentCache,
+    subjects::KnownSubject,
+    verification::{extract_holder, extract_issuer, presentation_nonce, Verifier},
+};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    policy::HolderBinding,
+    report::{Check, CredentialClaims, ErrorCode, VerificationReport},
+    schema::SchemaRegistry,
+    wallet::{credential_types, decode_jwt_claims},
+};
+
+/// What a scenario's verifier requires of a presentation and of the credentials in it.
+pub(crate) trait VerifierRules {
+    /// Nonce the VP must be signed with.
+    fn challenge(&self) -> &str;
+
+    /// Audience the VP must be addressed to; `None` accepts any.
+    fn audience(&self) -> Option<&str> {
+        None
+    }
+
+    /// Longest span between the VP's issuance and expiry; `None` disables the check.
+    fn max_presentation_lifetime_minutes(&self) -> Option<i64> {
+        None
+    }
+
+    /// How the subject of a credential of `credential_types` must relate to the holder.
+    fn holder_binding(&self, credential_types: &[String]) -> HolderBinding;
+
+    /// Whether `issuer` may issue a credential of `credential_types`, with the reason either way.
+    fn trust(&self, credential_types: &[String], issuer: &CoreDID) -> Result<String, String>;
+}
+
+/// Per-request switches of [`verify_presentation`].
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct VerifyOptions {
+    /// Validate every credential and report all failures instead of stopping at the first one.
+    pub collect_all_errors: bool,
+    /// Resolve the holder and issuers from the network instead of the DID cache.
+    pub force_fresh: bool,
+}
+
+/// Checks `vp_jwt` and every credential in it under `rules`, recording each check in `report` and the
+/// claims of each credential that passed. Returns the resolved issuer documents, or the status to
+/// answer with if verification stopped early.
+pub(crate) async fn verify_presentation(
+    vp_jwt: &Jwt,
+    rules: &impl VerifierRules,
+    schemas: &SchemaRegistry,
+    did_cache: &Arc<DocumentCache>,
+    options: VerifyOptions,
+    report: &mut VerificationReport,
+) -> Result<HashMap<CoreDID, CoreDocument>, StatusCode> {
+    // 1. Set up the verifier; it only connects to the network for did:iota documents missing from the cache
+    let verifier = Verifier::new().with_cache(did_cache.clone(), options.force_fresh);
+
+    // 2. Resolve Holder DID
+    let holder_did: CoreDID = match extract_holder(vp_jwt) {
+        Ok(holder_did) => holder_did,
+        Err(e) => {
+            report.fail(Check::HolderResolution, None, ErrorCode::MalformedPresentation, e.to_string());
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+    report.holder = Some(holder_did.to_string());
+
+    let holder_doc: CoreDocument = match verifier.resolve(&holder_did).await {
+        Ok(holder_doc) => holder_doc,
+        Err(e) => {
+            report.fail(Check::HolderResolution, None, ErrorCode::HolderUnresolvable, e.to_string());
+            return Err(StatusCode::OK);
+        }
+    };
+    report.pass(Check::HolderResolution, None, format!("resolved {}", holder_doc.id()));
+
+    // 3. Check the challenge signed into the VP header
+    let challenge = rules.challenge();
+    match presentation_nonce(vp_jwt).as_deref() {
+        Some(nonce) if nonce == challenge => report.pass(Check::Nonce, None, format!("matches challenge {}", challenge)),
+        Some(nonce) => report.fail(Check::Nonce, None, ErrorCode::NonceMismatch, format!("expected {}, got {}", challenge, nonce)),
+        None => report.fail(Check::Nonce, None, ErrorCode::NonceMismatch, "VP carries no nonce"),
+    }
+
+    // 4. Validate VP Signature and Expiry (the nonce was checked above)
+    let decoded_vp: DecodedJwtPresentation<Jwt> = match verifier.validate_presentation(vp_jwt, &holder_doc, None) {
+        Ok(decoded_vp) => decoded_vp,
+        Err(e) => {
+            for error in &e.presentation_validation_errors {
+                report.fail_validation(Check::PresentationSignature, None, error);
+            }
+            return Err(StatusCode::OK);
+        }
+    };
+    report.pass(Check::PresentationSignature, None, format!("signed by holder {}", decoded_vp.presentation.holder));
+    report.pass(Check::Expiry, None, "presentation has not expired");
+
+    // 5. Check the presentation was addressed to us and is not valid for longer than we allow
+    if let Some(expected) = rules.audience() {
+        match decoded_vp.aud.as_ref() {
+            Some(aud) if Url::parse(expected).map_or(false, |expected| &expected == aud) => {
+                report.pass(Check::Audience, None, format!("addressed to {}", aud));
+            }
+            Some(aud) => {
+                report.fail(Check::Audience, None, ErrorCode::AudienceMismatch, format!("addressed to {}, expected {}", aud, expected));
+            }
+            None => {
+                report.fail(Check::Audience, None, ErrorCode::AudienceMismatch, format!("VP has no audience, expected {}", expected));
+            }
+        }
+    }
+
+    if let Some(max_minutes) = rules.max_presentation_lifetime_minutes() {
+        let lifetime_minutes = match (decoded_vp.issuance_date, decoded_vp.expiration_date) {
+            (Some(issued), Some(expires)) => Some((expires.to_unix() - issued.to_unix()) / 60),
+            _ => None,
+        };
+        match lifetime_minutes {
+            Some(minutes) if minutes <= max_minutes => {
+                report.pass(Check::Lifetime, None, format!("lifetime of {} minute(s) is within {}", minutes, max_minutes));
+            }
+            Some(minutes) => {
+                report.fail(Check::Lifetime, None, ErrorCode::LifetimeExceeded, format!("lifetime of {} minute(s) exceeds {}", minutes, max_minutes));
+            }
+            None => {
+                report.fail(Check::Lifetime, None, ErrorCode::LifetimeExceeded, "VP has no bounded lifetime");
+            }
+        }
+    }
+
+    // 6. Extract and Resolve the Issuers of the Embedded Credentials
+    let jwt_credentials: &Vec<Jwt> = &decoded_vp.presentation.verifiable_credential;
+
+    let mut issuers: Vec<Option<CoreDID>> = Vec::with_capacity(jwt_credentials.len());
+    for (index, jwt_vc) in jwt_credentials.iter().enumerate() {
+        match extract_issuer(jwt_vc) {
+            Ok(issuer) => issuers.push(Some(issuer)),
+            Err(e) => {
+                report.fail(Check::IssuerResolution, Some(index), ErrorCode::MalformedPresentation, e.to_string());
+                if !options.collect_all_errors {
+                    return Err(StatusCode::OK);
+                }
+                issuers.push(None);
+            }
+        }
+    }
+
+    // When collecting all errors, an unresolvable issuer only fails the credentials it issued
+    let mut issuers_documents: HashMap<CoreDID, CoreDocument> = HashMap::new();
+    if options.collect_all_errors {
+        for (index, issuer) in issuers.iter_mut().enumerate() {
+            let Some(issuer_did) = issuer.clone() else {
+                continue;
+            };
+            if issuers_documents.contains_key(&issuer_did) {
+                continue;
+            }
+            match verifier.resolve(&issuer_did).await {
+                Ok(issuer_doc) => {
+                    issuers_documents.insert(issuer_did, issuer_doc);
+                }
+                Err(e) => {
+                    report.fail(Check::IssuerResolution, Some(index), ErrorCode::IssuerUnresolvable, e.to_string());
+                    report.skip(Check::Trust, Some(index), "issuer could not be resolved");
+                    *issuer = None;
+                }
+            }
+        }
+    } else {
+        let known_issuers: Vec<CoreDID> = issuers.iter().flatten().cloned().collect();
+        issuers_documents = match verifier.resolve_multiple(&known_issuers).await {
+            Ok(issuers_documents) => issuers_documents,
+            Err(e) => {
+                report.fail(Check::IssuerResolution, None, ErrorCode::IssuerUnresolvable, e.to_string());
+                return Err(StatusCode::OK);
+            }
+        };
+    }
+
+    // 7. Validate Each Credential and the Trust in its Issuer
+    let fail_fast = if options.collect_all_errors { FailFast::AllErrors } else { FailFast::FirstError };
+
+    for (index, jwt_vc) in jwt_credentials.iter().enumerate() {
+        let Some(issuer_did) = &issuers[index] else {
+            continue;
+        };
+        let issuer_doc = &issuers_documents[issuer_did];
+        report.pass(Check::IssuerResolution, Some(index), format!("resolved {}", issuer_did));
+
+        // The holder binding depends on the credential type, which is read before validation
+        let claimed_types = decode_jwt_claims(jwt_vc.as_str())
+            .map(|claims| credential_types(&claims))
+            .unwrap_or_default();
+        let holder_binding: HolderBinding = rules.holder_binding(&claimed_types);
+
+        let result: Result<DecodedJwtCredential<Object>, _> =
+            verifier.validate_credential(jwt_vc, issuer_doc, &holder_did, holder_binding.into(), fail_fast);
+
+        let decoded_credential = match result {
+            Ok(decoded_credential) => decoded_credential,
+            Err(e) => {
+                for error in &e.validation_errors {
+                    report.fail_validation(Check::CredentialSignature, Some(index), error);
+                }
+                if options.collect_all_errors {
+                    report.skip(Check::Trust, Some(index), "credential did not validate");
+                    continue;
+                }
+                return Err(StatusCode::OK);
+            }
+        };
+        let credential = &decoded_credential.credential;
+
+        report.pass(Check::CredentialSignature, Some(index), format!("signed by {}", issuer_did));
+        report.pass(Check::Expiry, Some(index), "credential is within its validity period");
+        report.pass(Check::SubjectHolderBinding, Some(index), format!("{:?} policy satisfied for holder {}", holder_binding, holder_did));
+        if credential.credential_status.is_some() {
+            report.pass(Check::Status, Some(index), "credential is not revoked");
+        } else {
+            report.skip(Check::Status, Some(index), "credential declares no status");
+        }
+
+        // The signature says nothing about the shape of the claims, so check them against the type's schema
+        let claims = serde_json::to_value(&credential.credential_subject).unwrap_or_default();
+        let subjects = match &claims {
+            serde_json::Value::Array(subjects) => subjects.clone(),
+            subject => vec![subject.clone()],
+        };
+        match schemas.for_types(&credential.types) {
+            Some((credential_type, schema)) => {
+                let violations: Vec<String> = subjects
+                    .iter()
+                    .filter_map(|subject| schema.validate(subject).err())
+                    .flatten()
+                    .collect();
+                if violations.is_empty() {
+                    report.pass(Check::Schema, Some(index), format!("subject conforms to {} schema", credential_type));
+                } else {
+                    report.fail(Check::Schema, Some(index), ErrorCode::SchemaViolation, format!("subject does not conform to {}: {}", schema.id(), violations.join("; ")));
+                    if !options.collect_all_errors {
+                        return Err(StatusCode::OK);
+                    }
+                }
+            }
+            None => report.skip(Check::Schema, Some(index), format!("no schema registered for {:?}", credential.types)),
+        }
+
+        // Decode the claims of known credential types into their typed subjects
+        let subject = match credential.credential_subject.iter().next().and_then(|subject| KnownSubject::decode(&credential.types, subject)) {
+            Some(Ok(subject)) => Some(subject),
+            Some(Err(e)) => {
+                report.fail(Check::Schema, Some(index), ErrorCode::SchemaViolation, e.to_string());
+                if !options.collect_all_errors {
+                    return Err(StatusCode::OK);
+                }
+                None
+            }
+            None => None,
+        };
+
+        // A valid signature only proves who issued it; the issuer must also be trusted for this type
+        match rules.trust(&credential.types, issuer_did) {
+            Ok(detail) => report.pass(Check::Trust, Some(index), detail),
+            Err(detail) => {
+                report.fail(Check::Trust, Some(index), ErrorCode::UntrustedIssuer, detail);
+                if !options.collect_all_errors {
+                    return Err(StatusCode::OK);
+                }
+            }
+        }
+
+        report.credentials.push(CredentialClaims {
+            index,
+            issuer: issuer_did.to_string(),
+            types: credential.types.clone(),
+            holder_binding,
+            claims,
+            subject,
+        });
+    }
+
+    Ok(issuers_documents)
+}
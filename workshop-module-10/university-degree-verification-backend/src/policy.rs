@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use identity_iota::credential::SubjectHolderRelationship;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path};
+
+/// How a credential's subject must relate to the holder presenting it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HolderBinding {
+    // Ordered from least to most strict
+    Any,
+    SubjectOnNonTransferable,
+    AlwaysSubject,
+}
+
+impl From<HolderBinding> for SubjectHolderRelationship {
+    fn from(binding: HolderBinding) -> Self {
+        match binding {
+            HolderBinding::Any => SubjectHolderRelationship::Any,
+            HolderBinding::SubjectOnNonTransferable => SubjectHolderRelationship::SubjectOnNonTransferable,
+            HolderBinding::AlwaysSubject => SubjectHolderRelationship::AlwaysSubject,
+        }
+    }
+}
+
+fn default_holder_binding() -> HolderBinding {
+    HolderBinding::AlwaysSubject
+}
+
+/// Audience presentations must name when neither the policy nor `VERIFIER_ORIGIN` sets one: this server.
+const DEFAULT_VERIFIER_ORIGIN: &str = "http://localhost:3001";
+
+fn default_audience() -> String {
+    std::env::var("VERIFIER_ORIGIN").unwrap_or_else(|_| DEFAULT_VERIFIER_ORIGIN.to_string())
+}
+
+/// Verifier settings applied per credential type, loaded from `verifier_policy.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifierPolicy {
+    #[serde(default = "default_holder_binding")]
+    default_holder_binding: HolderBinding,
+    #[serde(default)]
+    holder_binding: HashMap<String, HolderBinding>,
+    // Our own DID or URL; presentations addressed to anyone else are rejected
+    #[serde(default = "default_audience")]
+    audience: String,
+    #[serde(default = "default_max_presentation_lifetime_minutes")]
+    max_presentation_lifetime_minutes: Option<i64>,
+}
+
+fn default_max_presentation_lifetime_minutes() -> Option<i64> {
+    Some(60)
+}
+
+impl Default for VerifierPolicy {
+    fn default() -> Self {
+        VerifierPolicy {
+            default_holder_binding: default_holder_binding(),
+            holder_binding: HashMap::new(),
+            audience: default_audience(),
+            max_presentation_lifetime_minutes: default_max_presentation_lifetime_minutes(),
+        }
+    }
+}
+
+impl VerifierPolicy {
+    /// Loads the policy from `path`, falling back to requiring the holder to be the subject.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(VerifierPolicy::default());
+        }
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).with_context(|| format!("invalid verifier policy {}", path.display()))
+    }
+
+    /// Returns the strictest binding configured for any of the credential's types.
+    pub fn holder_binding(&self, credential_types: &[String]) -> HolderBinding {
+        credential_types
+            .iter()
+            .filter_map(|credential_type| self.holder_binding.get(credential_type))
+            .copied()
+            .max()
+            .unwrap_or(self.default_holder_binding)
+    }
+
+    /// The audience presentations must be addressed to; defaults to `VERIFIER_ORIGIN`, or this server.
+    pub fn audience(&self) -> &str {
+        &self.audience
+    }
+
+    /// Longest span between a presentation's issuance and expiry we accept; `None` disables the check.
+    pub fn max_presentation_lifetime_minutes(&self) -> Option<i64> {
+        self.max_presentation_lifetime_minutes
+    }
+}
@@ -0,0 +1,203 @@
+use identity_iota::credential::JwtValidationError;
+use identity_logic::subjects::KnownSubject;
+use serde::Serialize;
+
+use crate::{domain_linkage::VerifiedOrigin, policy::HolderBinding};
+
+/// Individual checks the verifier performs on a presentation and its credentials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Check {
+    HolderResolution,
+    PresentationSignature,
+    Nonce,
+    Expiry,
+    Lifetime,
+    Audience,
+    IssuerResolution,
+    CredentialSignature,
+    SubjectHolderBinding,
+    Status,
+    Schema,
+    Trust,
+    RequiredCredentials,
+    DomainLinkage,
+    Product,
+    CustodyChain,
+}
+
+impl Check {
+    fn label(self) -> &'static str {
+        match self {
+            Check::HolderResolution => "Holder DID resolution",
+            Check::PresentationSignature => "VP signature",
+            Check::Nonce => "VP nonce",
+            Check::Expiry => "Expiry",
+            Check::Lifetime => "VP lifetime",
+            Check::Audience => "VP audience",
+            Check::IssuerResolution => "Issuer DID resolution",
+            Check::CredentialSignature => "Credential signature",
+            Check::SubjectHolderBinding => "Subject-holder binding",
+            Check::Status => "Credential status",
+            Check::Schema => "Credential schema",
+            Check::Trust => "Issuer trust",
+            Check::RequiredCredentials => "Required credentials",
+            Check::DomainLinkage => "Domain linkage",
+            Check::Product => "Certified product",
+            Check::CustodyChain => "Chain of custody",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Machine-readable reasons a check failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    MalformedPresentation,
+    HolderUnresolvable,
+    InvalidPresentationSignature,
+    NonceMismatch,
+    Expired,
+    NotYetValid,
+    LifetimeExceeded,
+    AudienceMismatch,
+    IssuerUnresolvable,
+    InvalidCredentialSignature,
+    SubjectHolderMismatch,
+    CredentialRevoked,
+    InvalidStatus,
+    SchemaViolation,
+    UntrustedIssuer,
+    MissingCredential,
+    DomainLinkageInvalid,
+    ProductMismatch,
+    BrokenCustodyChain,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckResult {
+    pub check: Check,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    pub detail: String,
+}
+
+/// Claims of a credential whose signature verified.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialClaims {
+    pub index: usize,
+    pub issuer: String,
+    pub types: Vec<String>,
+    pub holder_binding: HolderBinding,
+    pub claims: serde_json::Value,
+    /// The claims decoded into their typed subject, for known credential types.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<KnownSubject>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReport {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holder: Option<String>,
+    pub checks: Vec<CheckResult>,
+    pub errors: Vec<ErrorCode>,
+    pub credentials: Vec<CredentialClaims>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub verified_origins: Vec<VerifiedOrigin>,
+    /// Human-readable summary derived from `checks`.
+    pub output: String,
+}
+
+impl VerificationReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pass(&mut self, check: Check, credential: Option<usize>, detail: impl Into<String>) {
+        self.push(check, CheckStatus::Passed, credential, None, detail.into());
+    }
+
+    pub fn fail(&mut self, check: Check, credential: Option<usize>, code: ErrorCode, detail: impl Into<String>) {
+        self.push(check, CheckStatus::Failed, credential, Some(code), detail.into());
+    }
+
+    pub fn skip(&mut self, check: Check, credential: Option<usize>, detail: impl Into<String>) {
+        self.push(check, CheckStatus::Skipped, credential, None, detail.into());
+    }
+
+    /// Records a validation error reported by the identity validators against the matching check.
+    pub fn fail_validation(&mut self, signature_check: Check, credential: Option<usize>, error: &JwtValidationError) {
+        let (check, code) = classify(signature_check, error);
+        self.fail(check, credential, code, error.to_string());
+    }
+
+    pub fn has_failures(&self) -> bool {
+        self.checks.iter().any(|check| check.status == CheckStatus::Failed)
+    }
+
+    fn push(&mut self, check: Check, status: CheckStatus, credential: Option<usize>, code: Option<ErrorCode>, detail: String) {
+        if let Some(code) = code {
+            if !self.errors.contains(&code) {
+                self.errors.push(code);
+            }
+        }
+        self.checks.push(CheckResult { check, status, credential, code, detail });
+    }
+
+    /// Derives the overall outcome and the human-readable summary from the recorded checks.
+    pub fn finish(mut self) -> Self {
+        self.success = !self.checks.is_empty() && !self.has_failures();
+        self.output = self.summary();
+        self
+    }
+
+    fn summary(&self) -> String {
+        let mut output = String::new();
+        for result in &self.checks {
+            let icon = match result.status {
+                CheckStatus::Passed => "✅",
+                CheckStatus::Failed => "❌",
+                CheckStatus::Skipped => "⏭️",
+            };
+            let scope = result
+                .credential
+                .map(|index| format!("Credential [{}] ", index + 1))
+                .unwrap_or_default();
+            output.push_str(&format!("{} {}{}: {}\n", icon, scope, result.check.label(), result.detail));
+        }
+
+        if self.success {
+            output.push_str("\n🎉 All credentials in the VP are valid!");
+        } else {
+            output.push_str(&format!("\n❌ Verification failed: {:?}", self.errors));
+        }
+        output
+    }
+}
+
+fn classify(signature_check: Check, error: &JwtValidationError) -> (Check, ErrorCode) {
+    match error {
+        JwtValidationError::ExpirationDate => (Check::Expiry, ErrorCode::Expired),
+        JwtValidationError::IssuanceDate => (Check::Expiry, ErrorCode::NotYetValid),
+        JwtValidationError::SubjectHolderRelationship => (Check::SubjectHolderBinding, ErrorCode::SubjectHolderMismatch),
+        JwtValidationError::Revoked => (Check::Status, ErrorCode::CredentialRevoked),
+        JwtValidationError::InvalidStatus(_) => (Check::Status, ErrorCode::InvalidStatus),
+        _ if signature_check == Check::PresentationSignature => (signature_check, ErrorCode::InvalidPresentationSignature),
+        _ => (signature_check, ErrorCode::InvalidCredentialSignature),
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::{Context, Result};
+use identity_iota::{
+    credential::{RevocationBitmap, RevocationBitmapStatus, Status},
+    did::{DIDUrl, DID},
+    iota::IotaDocument,
+};
+use identity_logic::{
+    cache::DocumentCache,
+    get_controller_client, publish_did_update,
+    store::{load_controller_key, save_did_document, VaultSession},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::ApiError;
+
+/// How credentials issued from a template can be revoked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum RevocationMechanism {
+    /// Credentials carry no `credentialStatus` and cannot be revoked.
+    #[default]
+    None,
+    /// Credentials point at a bit in the `RevocationBitmap2022` service `service` of the issuer DID.
+    RevocationBitmap2022 { service: String },
+}
+
+/// Next free revocation bitmap index per issuer service, persisted so indices are never reused.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RevocationIndices {
+    #[serde(skip)]
+    path: PathBuf,
+    next_index: BTreeMap<String, u32>,
+}
+
+impl RevocationIndices {
+    /// Loads the indices from `path`, starting at 0 for every service if the file does not exist yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut indices = if path.exists() {
+            let json = fs::read_to_string(&path)?;
+            serde_json::from_str::<RevocationIndices>(&json)
+                .with_context(|| format!("invalid revocation indices {}", path.display()))?
+        } else {
+            RevocationIndices::default()
+        };
+        indices.path = path;
+        Ok(indices)
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write revocation indices {}", self.path.display()))
+    }
+
+    /// Reserves the next index of `service` and persists it before it is handed out. The indices are
+    /// only changed once the reservation is saved.
+    pub fn allocate(&mut self, service: &DIDUrl) -> Result<u32> {
+        let mut updated = self.clone();
+        let next = updated.next_index.entry(service.to_string()).or_insert(0);
+        let index = *next;
+        *next += 1;
+        updated.save()?;
+        *self = updated;
+        Ok(index)
+    }
+}
+
+/// Returns the URL of the revocation service `fragment` of the issuer DID.
+pub fn revocation_service_url(issuer_doc: &IotaDocument, fragment: &str) -> Result<DIDUrl, ApiError> {
+    issuer_doc
+        .id()
+        .to_url()
+        .join(format!("#{}", fragment.trim_start_matches('#')))
+        .map_err(ApiError::identity)
+}
+
+/// Same as [`revocation_service_url`], requiring the service to already be published in the DID document.
+pub fn revocation_service(issuer_doc: &IotaDocument, fragment: &str) -> Result<DIDUrl, ApiError> {
+    let service_url = revocation_service_url(issuer_doc, fragment)?;
+    if !has_service(issuer_doc, &service_url) {
+        return Err(ApiError::Configuration(anyhow::anyhow!(
+            "issuer DID document has no revocation service {}; publish it with a batch issuance first",
+            service_url
+        )));
+    }
+    Ok(service_url)
+}
+
+fn has_service(issuer_doc: &IotaDocument, service_url: &DIDUrl) -> bool {
+    issuer_doc.service().iter().any(|service| service.id() == service_url)
+}
+
+/// Builds the `credentialStatus` entry for bit `index` of the revocation service.
+pub fn bitmap_status(service_url: DIDUrl, index: u32) -> Status {
+    RevocationBitmapStatus::new(service_url, index).into()
+}
+
+/// Makes sure the issuer's published DID document contains the revocation service, adding an empty
+/// bitmap in a single DID update if it does not. Returns whether an update was published.
+pub async fn publish_revocation_service(
+    vault: &VaultSession,
+    did_cache: &DocumentCache,
+    doc_file: &str,
+    issuer_doc: &IotaDocument,
+    service_url: &DIDUrl,
+) -> Result<bool, ApiError> {
+    publish_revocations(vault, did_cache, doc_file, issuer_doc, service_url, &[]).await
+}
+
+/// Revokes the credentials at `indices` of the revocation service, creating the service if needed, and
+/// publishes the bitmap in a single DID update, dropping the issuer's document from `did_cache` so
+/// verifiers see the new bitmap. Returns whether an update was published.
+pub async fn publish_revocations(
+    vault: &VaultSession,
+    did_cache: &DocumentCache,
+    doc_file: &str,
+    issuer_doc: &IotaDocument,
+    service_url: &DIDUrl,
+    indices: &[u32],
+) -> Result<bool, ApiError> {
+    let controller = load_controller_key(doc_file)?;
+    let client = get_controller_client(&vault.storage, &controller).await?;
+
+    // Start from the latest published version rather than the local copy
+    let mut document = client.resolve_did(issuer_doc.id()).await.map_err(ApiError::identity)?;
+    let created = !has_service(&document, service_url);
+    if !created && indices.is_empty() {
+        return Ok(false);
+    }
+
+    if created {
+        let service = RevocationBitmap::new()
+            .to_service(service_url.clone())
+            .map_err(ApiError::identity)?;
+        document.insert_service(service).map_err(ApiError::identity)?;
+    }
+    if !indices.is_empty() {
+        document.revoke_credentials(service_url, indices).map_err(ApiError::identity)?;
+    }
+
+    let published = publish_did_update(&client, document).await?;
+    did_cache.purge(Some(published.id().as_ref()));
+    save_did_document(doc_file, &published)?;
+    if created {
+        println!(">> Published revocation service {}", service_url);
+    }
+    if !indices.is_empty() {
+        println!(">> Revoked {:?} in {}", indices, service_url);
+    }
+    Ok(true)
+}
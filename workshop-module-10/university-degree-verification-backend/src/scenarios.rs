@@ -0,0 +1,693 @@
+//! Scenarios described in `scenarios/<name>.toml` (or `.json`): the roles taking part, which role issues which credential
+//! template to whom, and which role verifies what under which policy. Routes, identities and wallets are
+//! generated from the description, so a new use case needs a scenario file and a template but no handlers.
+
+use anyhow::{bail, Context, Result};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use identity_iota::{
+    core::{Duration, Timestamp},
+    credential::{Jwt, JwtPresentationOptions},
+    did::CoreDID,
+};
+use identity_logic::{
+    presentation::create_presentation,
+    store::{create_or_load_identity, load_existing_did, open_vault, IdentityFiles},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    sync::Arc,
+};
+use tokio::sync::RwLock;
+
+use crate::{
+    error::ApiError,
+    pipeline::{verify_presentation, VerifierRules, VerifyOptions},
+    policy::HolderBinding,
+    report::{Check, ErrorCode, VerificationReport},
+    templates::{IssuanceParties, IssueFromTemplate, IssuedCredential, TemplateIssuer, TemplateRegistry},
+    wallet::{CredentialWallet, StoredCredential},
+    AppState,
+};
+
+/// A party taking part in a scenario.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleConfig {
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A role issuing credentials from a template to another role.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuanceFlow {
+    pub issuer: String,
+    pub holder: String,
+    /// Name of the credential template in `templates/`.
+    pub template: String,
+}
+
+fn default_holder_binding() -> HolderBinding {
+    HolderBinding::AlwaysSubject
+}
+
+/// A role verifying presentations, and the policy it applies to them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationFlow {
+    pub verifier: String,
+    /// Credential types a presentation must contain, each issued by one of `trustedIssuers`.
+    pub credential_types: Vec<String>,
+    /// Roles whose DIDs are trusted to issue those credentials.
+    pub trusted_issuers: Vec<String>,
+    #[serde(default = "default_holder_binding")]
+    pub holder_binding: HolderBinding,
+    #[serde(default)]
+    pub max_presentation_lifetime_minutes: Option<i64>,
+    /// Nonce presentations must be signed with; defaults to `<scenario>-<verifier>`.
+    #[serde(default)]
+    pub challenge: Option<String>,
+}
+
+/// A use case described declaratively, loaded from `scenarios/<name>.toml` or `.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioConfig {
+    /// File stem; routes are served under `/api/scenarios/<name>`.
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub roles: BTreeMap<String, RoleConfig>,
+    #[serde(default)]
+    pub issuance: Vec<IssuanceFlow>,
+    #[serde(default)]
+    pub verification: Vec<VerificationFlow>,
+}
+
+/// An operation generated for a scenario, served at its own route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Action {
+    CreateDid { role: String },
+    Issue { template: String },
+    Credentials { role: String },
+    Present { role: String },
+    Verify { verifier: String },
+}
+
+impl Action {
+    fn route(&self, scenario: &str) -> (&'static str, String) {
+        match self {
+            Action::CreateDid { role } => ("POST", format!("/api/scenarios/{}/{}/create-did", scenario, role)),
+            Action::Issue { template } => ("POST", format!("/api/scenarios/{}/issue/{}", scenario, template)),
+            Action::Credentials { role } => ("GET", format!("/api/scenarios/{}/{}/credentials", scenario, role)),
+            Action::Present { role } => ("POST", format!("/api/scenarios/{}/{}/present", scenario, role)),
+            Action::Verify { verifier } => ("POST", format!("/api/scenarios/{}/verify/{}", scenario, verifier)),
+        }
+    }
+}
+
+impl ScenarioConfig {
+    fn validate(&self, templates: &TemplateRegistry) -> Result<()> {
+        IdentityFiles::named(&self.name)?;
+        if self.roles.is_empty() {
+            bail!("a scenario needs at least one role");
+        }
+        for role in self.roles.keys() {
+            IdentityFiles::named(role).with_context(|| format!("invalid role {}", role))?;
+        }
+        let check_role = |role: &str| match self.roles.contains_key(role) {
+            true => Ok(()),
+            false => Err(anyhow::anyhow!("unknown role {}", role)),
+        };
+
+        for (index, flow) in self.issuance.iter().enumerate() {
+            check_role(&flow.issuer)?;
+            check_role(&flow.holder)?;
+            if templates.get(&flow.template).is_none() {
+                bail!("unknown template {}", flow.template);
+            }
+            if self.issuance[..index].iter().any(|other| other.template == flow.template) {
+                bail!("template {} is issued by more than one flow", flow.template);
+            }
+        }
+
+        for (index, flow) in self.verification.iter().enumerate() {
+            check_role(&flow.verifier)?;
+            for issuer in &flow.trusted_issuers {
+                check_role(issuer)?;
+            }
+            if flow.credential_types.is_empty() {
+                bail!("verifier {} requires no credential types", flow.verifier);
+            }
+            if matches!(flow.max_presentation_lifetime_minutes, Some(minutes) if minutes <= 0) {
+                bail!("maxPresentationLifetimeMinutes of verifier {} must be positive", flow.verifier);
+            }
+            if self.verification[..index].iter().any(|other| other.verifier == flow.verifier) {
+                bail!("role {} has more than one verification flow", flow.verifier);
+            }
+        }
+        Ok(())
+    }
+
+    /// Files of a role's identity, e.g. `./employee-badge-employer_doc.json`.
+    pub fn identity(&self, role: &str) -> identity_logic::Result<IdentityFiles> {
+        IdentityFiles::named(&format!("{}-{}", self.name, role))
+    }
+
+    fn wallet_file(&self, role: &str) -> String {
+        format!("./{}-{}_credentials.json", self.name, role)
+    }
+
+    /// Roles that receive credentials and present them.
+    fn holders(&self) -> Vec<&str> {
+        let mut holders: Vec<&str> = self.issuance.iter().map(|flow| flow.holder.as_str()).collect();
+        holders.sort_unstable();
+        holders.dedup();
+        holders
+    }
+
+    fn challenge(&self, flow: &VerificationFlow) -> String {
+        flow.challenge
+            .clone()
+            .unwrap_or_else(|| format!("{}-{}", self.name, flow.verifier))
+    }
+
+    /// Every operation the scenario supports: DIDs for signing roles, issuance per template,
+    /// wallets and presentations for holders, and verification per verifier.
+    fn actions(&self) -> Vec<Action> {
+        let holders = self.holders();
+        let mut actions = Vec::new();
+        for role in self.roles.keys() {
+            let issues = self.issuance.iter().any(|flow| &flow.issuer == role);
+            if issues || holders.contains(&role.as_str()) {
+                actions.push(Action::CreateDid { role: role.clone() });
+            }
+        }
+        for flow in &self.issuance {
+            actions.push(Action::Issue { template: flow.template.clone() });
+        }
+        for role in &holders {
+            actions.push(Action::Credentials { role: role.to_string() });
+            actions.push(Action::Present { role: role.to_string() });
+        }
+        for flow in &self.verification {
+            actions.push(Action::Verify { verifier: flow.verifier.clone() });
+        }
+        actions
+    }
+}
+
+/// Configured scenarios keyed by name, with the credential wallet of every holder role.
+#[derive(Debug, Default)]
+pub struct ScenarioRegistry {
+    scenarios: BTreeMap<String, ScenarioConfig>,
+    // Keyed by (scenario, role)
+    wallets: HashMap<(String, String), RwLock<CredentialWallet>>,
+}
+
+impl ScenarioRegistry {
+    /// Loads every `.toml` and `.json` scenario in `dir`, checking the templates it uses exist; a missing
+    /// directory yields no scenarios.
+    pub fn load(dir: impl AsRef<Path>, templates: &TemplateRegistry) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut registry = ScenarioRegistry::default();
+        if !dir.exists() {
+            return Ok(registry);
+        }
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()).map(str::to_owned) else {
+                continue;
+            };
+            let mut scenario: ScenarioConfig = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("toml") => toml::from_str(&fs::read_to_string(&path)?)
+                    .with_context(|| format!("invalid scenario {}", path.display()))?,
+                Some("json") => serde_json::from_str(&fs::read_to_string(&path)?)
+                    .with_context(|| format!("invalid scenario {}", path.display()))?,
+                _ => continue,
+            };
+            scenario.name = name.clone();
+            scenario.validate(templates).with_context(|| format!("invalid scenario {}", path.display()))?;
+
+            for role in scenario.holders() {
+                let wallet = CredentialWallet::load(scenario.wallet_file(role))?;
+                registry.wallets.insert((name.clone(), role.to_string()), RwLock::new(wallet));
+            }
+            if registry.scenarios.insert(name.clone(), scenario).is_some() {
+                bail!("scenario {} is defined more than once in {}", name, dir.display());
+            }
+        }
+        Ok(registry)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ScenarioConfig> {
+        self.scenarios.get(name)
+    }
+
+    pub fn list(&self) -> Vec<&ScenarioConfig> {
+        self.scenarios.values().collect()
+    }
+
+    fn wallet(&self, scenario: &str, role: &str) -> Option<&RwLock<CredentialWallet>> {
+        self.wallets.get(&(scenario.to_string(), role.to_string()))
+    }
+}
+
+// --- Scenario API ---
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioSummary {
+    #[serde(flatten)]
+    scenario: ScenarioConfig,
+    // "METHOD /path" of every generated route
+    endpoints: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenariosResponse {
+    scenarios: Vec<ScenarioSummary>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleDid {
+    role: String,
+    did: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleCredentials {
+    role: String,
+    credentials: Vec<StoredCredential>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresentRequest {
+    // Verifier role the presentation is for; its challenge is signed into the VP
+    verifier: String,
+    // Wallet credentials to present; defaults to all of the types the verifier requires
+    #[serde(default)]
+    credential_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresentationResponse {
+    jwt: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyRequest {
+    vp_jwt: String,
+    // Resolve the holder and issuers from the network instead of the DID cache
+    #[serde(default)]
+    force_fresh: bool,
+}
+
+// Routes are generated from the loaded scenarios, so the scenario is always there
+fn scenario<'a>(state: &'a AppState, name: &str) -> &'a ScenarioConfig {
+    state.scenarios.get(name).expect("routes are generated from loaded scenarios")
+}
+
+async fn list_scenarios(State(state): State<Arc<AppState>>) -> Json<ScenariosResponse> {
+    let scenarios = state
+        .scenarios
+        .list()
+        .into_iter()
+        .map(|scenario| ScenarioSummary {
+            endpoints: scenario
+                .actions()
+                .iter()
+                .map(|action| {
+                    let (method, path) = action.route(&scenario.name);
+                    format!("{} {}", method, path)
+                })
+                .collect(),
+            scenario: scenario.clone(),
+        })
+        .collect();
+    Json(ScenariosResponse { scenarios })
+}
+
+// Creates (or loads) the DID of a role that signs credentials or presentations
+async fn create_role_did(state: Arc<AppState>, scenario_name: String, role: String) -> Result<Json<RoleDid>, ApiError> {
+    let scenario = scenario(&state, &scenario_name);
+    let (doc, _) = create_or_load_identity(&scenario.identity(&role)?).await?;
+    Ok(Json(RoleDid {
+        role,
+        did: doc.id().to_string(),
+    }))
+}
+
+// Issues a credential from the flow's template, signed by the issuer role and delivered to the holder role
+async fn issue(
+    state: Arc<AppState>,
+    scenario_name: String,
+    template_name: String,
+    body: IssueFromTemplate,
+) -> Result<Json<IssuedCredential>, ApiError> {
+    let scenario = scenario(&state, &scenario_name);
+    let flow = scenario
+        .issuance
+        .iter()
+        .find(|flow| flow.template == template_name)
+        .expect("routes are generated from the scenario's issuance flows");
+
+    let issuer = scenario.identity(&flow.issuer)?;
+    let parties = IssuanceParties {
+        issuer: issuer.clone(),
+        holder_doc_file: scenario.identity(&flow.holder)?.doc_file,
+        holder_wallet: state
+            .scenarios
+            .wallet(&scenario.name, &flow.holder)
+            .expect("every holder role has a wallet"),
+    };
+    let template_issuer = TemplateIssuer::with_parties(&state, &flow.template, parties, false).await?;
+    let vault = open_vault(&issuer.stronghold_path).await?;
+    let issued = template_issuer.issue(&vault, &body).await?;
+    Ok(Json(issued))
+}
+
+async fn list_role_credentials(state: Arc<AppState>, scenario_name: String, role: String) -> Json<RoleCredentials> {
+    let wallet = state
+        .scenarios
+        .wallet(&scenario_name, &role)
+        .expect("every holder role has a wallet")
+        .read()
+        .await;
+    Json(RoleCredentials {
+        credentials: wallet.list().to_vec(),
+        role,
+    })
+}
+
+// Presents credentials from the holder role's wallet to a verifier role
+async fn present(
+    state: Arc<AppState>,
+    scenario_name: String,
+    role: String,
+    body: PresentRequest,
+) -> Result<Json<PresentationResponse>, ApiError> {
+    let scenario = scenario(&state, &scenario_name);
+    let flow = scenario
+        .verification
+        .iter()
+        .find(|flow| flow.verifier == body.verifier)
+        .ok_or_else(|| ApiError::NotFound(format!("{} does not verify presentations in {}", body.verifier, scenario.name)))?;
+
+    // 1. Pick the credentials, by ID or by the types the verifier asks for
+    let credentials: Vec<Jwt> = {
+        let wallet = state
+            .scenarios
+            .wallet(&scenario.name, &role)
+            .expect("every holder role has a wallet")
+            .read()
+            .await;
+        if body.credential_ids.is_empty() {
+            wallet
+                .list()
+                .iter()
+                .filter(|stored| stored.types.iter().any(|credential_type| flow.credential_types.contains(credential_type)))
+                .map(|stored| Jwt::new(stored.jwt.clone()))
+                .collect()
+        } else {
+            body.credential_ids
+                .iter()
+                .map(|id| {
+                    wallet
+                        .get(id)
+                        .map(|stored| Jwt::new(stored.jwt.clone()))
+                        .ok_or_else(|| ApiError::NotFound(format!("credential {} not found in the {} wallet", id, role)))
+                })
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    // 2. Sign the VP with the verifier's challenge, valid for as long as the verifier accepts
+    let holder = scenario.identity(&role)?;
+    let (holder_doc, holder_fragment) = create_or_load_identity(&holder).await?;
+    let vault = open_vault(&holder.stronghold_path).await?;
+    let expires = Timestamp::now_utc()
+        .checked_add(Duration::minutes(flow.max_presentation_lifetime_minutes.unwrap_or(10)))
+        .ok_or_else(|| ApiError::InvalidInput("maxPresentationLifetimeMinutes is too large".to_string()))?;
+    let vp_jwt = create_presentation(
+        &holder_doc,
+        &vault.storage,
+        &holder_fragment,
+        credentials,
+        &scenario.challenge(flow),
+        &JwtPresentationOptions::default().expiration_date(expires),
+    )
+    .await?;
+
+    Ok(Json(PresentationResponse {
+        jwt: vp_jwt.as_str().to_string(),
+    }))
+}
+
+// A verification flow with its challenge and the DIDs of the roles it trusts
+struct FlowRules<'a> {
+    flow: &'a VerificationFlow,
+    challenge: String,
+    trusted_issuers: HashMap<String, &'a str>,
+}
+
+impl VerifierRules for FlowRules<'_> {
+    fn challenge(&self) -> &str {
+        &self.challenge
+    }
+
+    fn max_presentation_lifetime_minutes(&self) -> Option<i64> {
+        self.flow.max_presentation_lifetime_minutes
+    }
+
+    fn holder_binding(&self, _credential_types: &[String]) -> HolderBinding {
+        self.flow.holder_binding
+    }
+
+    fn trust(&self, _credential_types: &[String], issuer: &CoreDID) -> Result<String, String> {
+        match self.trusted_issuers.get(&issuer.to_string()) {
+            Some(role) => Ok(format!("issued by the {} role", role)),
+            None => Err(format!("{} is not one of {:?}", issuer, self.flow.trusted_issuers)),
+        }
+    }
+}
+
+// Verifies a presentation under the verifier role's policy, stopping at the first failed check
+async fn verify(
+    state: Arc<AppState>,
+    scenario_name: String,
+    verifier_role: String,
+    body: VerifyRequest,
+) -> Result<(StatusCode, Json<VerificationReport>), ApiError> {
+    let scenario = scenario(&state, &scenario_name);
+    let flow = scenario
+        .verification
+        .iter()
+        .find(|flow| flow.verifier == verifier_role)
+        .expect("routes are generated from the scenario's verification flows");
+    let vp_jwt = Jwt::new(body.vp_jwt);
+    let mut report = VerificationReport::new();
+
+    // 1. Check the presentation and its credentials, which must come from a trusted role. A trusted role
+    // without a readable DID document is a setup mistake, not an untrusted issuer
+    let trusted_issuers: HashMap<String, &str> = flow
+        .trusted_issuers
+        .iter()
+        .map(|role| {
+            let doc = scenario
+                .identity(role)
+                .and_then(|files| load_existing_did(&files.doc_file, role))
+                .with_context(|| format!("trusted issuer {} of verifier {} in {} has no DID", role, flow.verifier, scenario.name))
+                .map_err(ApiError::Configuration)?;
+            Ok::<_, ApiError>((doc.id().to_string(), role.as_str()))
+        })
+        .collect::<Result<_, _>>()?;
+    let rules = FlowRules {
+        flow,
+        challenge: scenario.challenge(flow),
+        trusted_issuers,
+    };
+    let options = VerifyOptions {
+        collect_all_errors: false,
+        force_fresh: body.force_fresh,
+    };
+    if let Err(status) = verify_presentation(&vp_jwt, &rules, &state.schemas, &state.did_cache, options, &mut report).await {
+        return Ok((status, Json(report.finish())));
+    }
+
+    // 2. Every credential type the verifier requires must have been presented
+    let missing: Vec<&str> = flow
+        .credential_types
+        .iter()
+        .filter(|required| !report.credentials.iter().any(|credential| credential.types.contains(required)))
+        .map(String::as_str)
+        .collect();
+    if missing.is_empty() {
+        report.pass(Check::RequiredCredentials, None, format!("presented {}", flow.credential_types.join(", ")));
+    } else {
+        report.fail(Check::RequiredCredentials, None, ErrorCode::MissingCredential, format!("missing {}", missing.join(", ")));
+    }
+
+    Ok((StatusCode::OK, Json(report.finish())))
+}
+
+/// Routes of every configured scenario, generated from its roles and flows, plus `GET /api/scenarios`.
+pub(crate) fn router(state: Arc<AppState>) -> Router {
+    let mut router = Router::new().route("/api/scenarios", get(list_scenarios));
+
+    for scenario in state.scenarios.list() {
+        for action in scenario.actions() {
+            let (_, path) = action.route(&scenario.name);
+            let name = scenario.name.clone();
+            router = match action {
+                Action::CreateDid { role } => router.route(
+                    &path,
+                    post(move |State(state): State<Arc<AppState>>| create_role_did(state, name, role)),
+                ),
+                Action::Issue { template } => router.route(
+                    &path,
+                    post(move |State(state): State<Arc<AppState>>, Json(body): Json<IssueFromTemplate>| {
+                        issue(state, name, template, body)
+                    }),
+                ),
+                Action::Credentials { role } => router.route(
+                    &path,
+                    get(move |State(state): State<Arc<AppState>>| list_role_credentials(state, name, role)),
+                ),
+                Action::Present { role } => router.route(
+                    &path,
+                    post(move |State(state): State<Arc<AppState>>, Json(body): Json<PresentRequest>| {
+                        present(state, name, role, body)
+                    }),
+                ),
+                Action::Verify { verifier } => router.route(
+                    &path,
+                    post(move |State(state): State<Arc<AppState>>, Json(body): Json<VerifyRequest>| {
+                        verify(state, name, verifier, body)
+                    }),
+                ),
+            };
+        }
+    }
+    router.with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn templates() -> TemplateRegistry {
+        TemplateRegistry::load("./templates").unwrap()
+    }
+
+    // The employee badge scenario, with `extra` added to the settings of its security verifier
+    fn scenario(extra: &str) -> ScenarioConfig {
+        let toml = format!(
+            r#"
+            [roles.employer]
+            [roles.employee]
+            [roles.security]
+
+            [[issuance]]
+            issuer = "employer"
+            holder = "employee"
+            template = "employee-badge"
+
+            [[verification]]
+            verifier = "security"
+            credentialTypes = ["EmployeeBadgeCredential"]
+            trustedIssuers = ["employer"]
+            {}
+            "#,
+            extra
+        );
+        let mut scenario: ScenarioConfig = toml::from_str(&toml).unwrap();
+        scenario.name = "employee-badge".to_string();
+        scenario
+    }
+
+    fn error(scenario: &ScenarioConfig) -> String {
+        scenario.validate(&templates()).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn accepts_the_bundled_scenarios() {
+        let registry = ScenarioRegistry::load("./scenarios", &templates()).unwrap();
+        assert!(registry.get("employee-badge").is_some());
+        assert!(scenario("").validate(&templates()).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_roles() {
+        let mut unknown_holder = scenario("");
+        unknown_holder.issuance[0].holder = "visitor".to_string();
+        assert_eq!(error(&unknown_holder), "unknown role visitor");
+
+        let mut unknown_verifier = scenario("");
+        unknown_verifier.verification[0].verifier = "reception".to_string();
+        assert_eq!(error(&unknown_verifier), "unknown role reception");
+
+        let mut unknown_issuer = scenario("");
+        unknown_issuer.verification[0].trusted_issuers.push("contractor".to_string());
+        assert_eq!(error(&unknown_issuer), "unknown role contractor");
+    }
+
+    #[test]
+    fn rejects_unknown_templates() {
+        let mut scenario = scenario("");
+        scenario.issuance[0].template = "parking-permit".to_string();
+        assert_eq!(error(&scenario), "unknown template parking-permit");
+    }
+
+    #[test]
+    fn rejects_duplicate_flows() {
+        let mut issued_twice = scenario("");
+        let flow = issued_twice.issuance[0].clone();
+        issued_twice.issuance.push(flow);
+        assert_eq!(error(&issued_twice), "template employee-badge is issued by more than one flow");
+
+        let mut verified_twice = scenario("");
+        let flow = verified_twice.verification[0].clone();
+        verified_twice.verification.push(flow);
+        assert_eq!(error(&verified_twice), "role security has more than one verification flow");
+    }
+
+    #[test]
+    fn rejects_non_positive_lifetimes() {
+        for minutes in [0, -5] {
+            let scenario = scenario(&format!("maxPresentationLifetimeMinutes = {}", minutes));
+            assert_eq!(error(&scenario), "maxPresentationLifetimeMinutes of verifier security must be positive");
+        }
+        assert!(scenario("maxPresentationLifetimeMinutes = 1").validate(&templates()).is_ok());
+    }
+
+    #[test]
+    fn rejects_verifiers_requiring_nothing() {
+        let mut scenario = scenario("");
+        scenario.verification[0].credential_types.clear();
+        assert_eq!(error(&scenario), "verifier security requires no credential types");
+    }
+
+    #[test]
+    fn rejects_invalid_role_names() {
+        let mut scenario = scenario("");
+        scenario.roles.insert("Front Desk".to_string(), RoleConfig::default());
+        assert_eq!(error(&scenario), "invalid role Front Desk");
+    }
+}
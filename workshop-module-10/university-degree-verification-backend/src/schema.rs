@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use axum::extract::{Json, Path as UrlPath, State};
+use identity_iota::{core::Url, credential::Schema};
+use jsonschema::Validator;
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
+
+use crate::{domain_linkage::issuer_origin, error::ApiError, AppState};
+
+/// `credentialSchema.type` for schemas validated with JSON Schema.
+pub const JSON_SCHEMA_TYPE: &str = "JsonSchema";
+
+/// A JSON Schema describing the `credentialSubject` of one credential type.
+pub struct CredentialSchema {
+    id: Url,
+    document: serde_json::Value,
+    validator: Validator,
+}
+
+impl CredentialSchema {
+    /// The `credentialSchema` entry added to credentials of this type.
+    pub fn reference(&self) -> Schema {
+        Schema::new(self.id.clone(), JSON_SCHEMA_TYPE.to_string())
+    }
+
+    pub fn id(&self) -> &Url {
+        &self.id
+    }
+
+    /// Checks a credential subject, returning one message per violation.
+    pub fn validate(&self, subject: &serde_json::Value) -> Result<(), Vec<String>> {
+        let violations: Vec<String> = self
+            .validator
+            .iter_errors(subject)
+            .map(|error| match error.instance_path.to_string() {
+                path if path.is_empty() => error.to_string(),
+                path => format!("{}: {}", path, error),
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// Credential schemas keyed by credential type, loaded from `<dir>/<CredentialType>.json`.
+#[derive(Default)]
+pub struct SchemaRegistry {
+    schemas: HashMap<String, CredentialSchema>,
+}
+
+impl SchemaRegistry {
+    /// Loads every schema in `dir`; a missing directory yields an empty registry.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut registry = SchemaRegistry::default();
+        if !dir.exists() {
+            return Ok(registry);
+        }
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(true, |extension| extension != "json") {
+                continue;
+            }
+            let Some(credential_type) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let json = fs::read_to_string(&path)?;
+            let document: serde_json::Value =
+                serde_json::from_str(&json).with_context(|| format!("invalid schema {}", path.display()))?;
+            let validator = jsonschema::validator_for(&document)
+                .map_err(|e| anyhow::anyhow!("invalid schema {}: {}", path.display(), e))?;
+
+            // The schema's own `$id` is used as its reference, otherwise it is served by this API
+            let id = match document.get("$id").and_then(|id| id.as_str()) {
+                Some(id) => Url::parse(id).with_context(|| format!("invalid $id in {}", path.display()))?,
+                None => issuer_origin()?.join(format!("/api/schemas/{}", credential_type))?,
+            };
+
+            registry
+                .schemas
+                .insert(credential_type.to_string(), CredentialSchema { id, document, validator });
+        }
+        Ok(registry)
+    }
+
+    pub fn get(&self, credential_type: &str) -> Option<&CredentialSchema> {
+        self.schemas.get(credential_type)
+    }
+
+    /// Returns the schema registered for the first of `credential_types` that has one.
+    pub fn for_types(&self, credential_types: &[String]) -> Option<(&str, &CredentialSchema)> {
+        credential_types
+            .iter()
+            .find_map(|credential_type| self.schemas.get_key_value(credential_type.as_str()))
+            .map(|(credential_type, schema)| (credential_type.as_str(), schema))
+    }
+}
+
+// --- Schema API ---
+
+pub async fn get_schema(
+    State(state): State<Arc<AppState>>,
+    UrlPath(credential_type): UrlPath<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    state
+        .schemas
+        .get(&credential_type)
+        .map(|schema| Json(schema.document.clone()))
+        .ok_or_else(|| ApiError::NotFound(format!("no schema registered for {}", credential_type)))
+}
@@ -0,0 +1,557 @@
+//! The supply-chain scenario: a manufacturer certifies a product's origin, custodians hand the product on,
+//! and the retailer verifies the chain of custody and the shipment events reported along the way.
+
+use axum::{
+    routing::post,
+    extract::{Path, State, Json},
+    http::StatusCode,
+    Router,
+};
+use identity_iota::{
+    core::{Duration, Timestamp, Url},
+    credential::{Jwt, JwtPresentationOptions, Subject},
+    did::{CoreDID, DID},
+};
+use identity_logic::{
+    cache::DocumentCache,
+    custody::{verify_chain, Handover},
+    issuance::{build_credential, credential_builder, sign_credential},
+    presentation::create_presentation,
+    store::{create_or_load_identity, load_existing_did, open_vault, IdentityFiles},
+    subjects::{CustodyHandoverSubject, KnownSubject, ProductOriginSubject, ShipmentEvent, ShipmentEventSubject, TypedSubject},
+    verification::Verifier,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::{
+    error::ApiError,
+    pipeline::{verify_presentation, VerifierRules, VerifyOptions},
+    policy::{HolderBinding, VerifierPolicy},
+    report::{Check, ErrorCode, VerificationReport},
+    schema::{CredentialSchema, SchemaRegistry},
+    trust_registry::TrustRegistry,
+    wallet::{credential_types, decode_jwt_claims},
+};
+
+// Challenge the retailer expects delivery presentations to be signed with
+const RETAILER_CHALLENGE: &str = "retailer-auth-nonce-999";
+
+// --- Data Structures ---
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RequestBody {
+    // Product the manufacturer certifies, and the product the retailer expects to receive
+    package_id: String,
+    vc_jwt: Option<String>,
+    vp_jwt: Option<String>,
+    // Chain of custody credentials to present alongside vc_jwt
+    vc_jwts: Option<Vec<String>>,
+    // Party presenting the credentials (defaults to the logistics provider)
+    holder: Option<String>,
+    // Minutes until the delivery presentation expires (defaults to 30)
+    expires_in_minutes: Option<i64>,
+    // The verifier's DID or URL the presentation is addressed to (defaults to the retailer's audience)
+    audience: Option<String>,
+    // Factory the retailer ordered from: the delivery is refused if the certified factory differs
+    expected_factory_origin: Option<String>,
+    // Resolve every DID from the network instead of the DID cache
+    #[serde(default)]
+    force_fresh: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HandoverBody {
+    // Party handing the product over, which must currently hold it
+    from: String,
+    // Party receiving the product
+    to: String,
+    // The chain received so far: the origin certificate followed by earlier handovers
+    vc_jwts: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventBody {
+    // Sensor or carrier reporting the event, e.g. "sensor-7"
+    reporter: String,
+    product_id: String,
+    event: ShipmentEvent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimelineBody {
+    product_id: String,
+    event_jwts: Vec<String>,
+    #[serde(default)]
+    force_fresh: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TimelineEntry {
+    occurred_at: String,
+    event_type: String,
+    reporter: String,
+    event: ShipmentEvent,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TimelineResponse {
+    product_id: String,
+    // Verified events for the product, oldest first
+    events: Vec<TimelineEntry>,
+    // Events that could not be verified, come from an untrusted reporter or belong to another product, with the reason
+    rejected: Vec<String>,
+    // False if any event reports a temperature excursion or a broken seal
+    intact: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiResponse {
+    did: Option<String>,
+    jwt: Option<String>,
+}
+
+impl ApiResponse {
+    fn did(did: String) -> Json<Self> {
+        Json(ApiResponse { did: Some(did), jwt: None })
+    }
+
+    fn jwt(jwt: Jwt) -> Json<Self> {
+        Json(ApiResponse { did: None, jwt: Some(jwt.as_str().to_string()) })
+    }
+}
+
+/// State of the supply-chain routes: the schemas origin certificates are checked against, the audience
+/// and presentation lifetime the retailer accepts, the issuers it trusts besides the manufacturer, and
+/// the cache of DID documents it resolved.
+pub(crate) struct SupplyChainState {
+    schemas: SchemaRegistry,
+    verifier_policy: VerifierPolicy,
+    trust_registry: Arc<RwLock<TrustRegistry>>,
+    did_cache: Arc<DocumentCache>,
+}
+
+impl SupplyChainState {
+    pub(crate) fn load(trust_registry: Arc<RwLock<TrustRegistry>>, did_cache: Arc<DocumentCache>) -> anyhow::Result<Self> {
+        Ok(SupplyChainState {
+            schemas: SchemaRegistry::load("./schemas")?,
+            verifier_policy: VerifierPolicy::load("./verifier_policy.json")?,
+            trust_registry,
+            did_cache,
+        })
+    }
+
+    fn product_origin_schema(&self) -> Result<&CredentialSchema, ApiError> {
+        let credential_type = ProductOriginSubject::CREDENTIAL_TYPE;
+        self.schemas
+            .get(credential_type)
+            .ok_or_else(|| ApiError::Configuration(anyhow::anyhow!("no schema registered for {}", credential_type)))
+    }
+}
+
+// --- Helpers ---
+
+// Reads the typed subject of a VC JWT without verifying it; the retailer verifies the whole chain later
+fn decode_chain_subject(jwt: &str) -> Result<KnownSubject, ApiError> {
+    let claims = decode_jwt_claims(jwt)?;
+    let types = credential_types(&claims);
+    let mut subject = claims
+        .get("vc")
+        .and_then(|vc| vc.get("credentialSubject"))
+        .cloned()
+        .ok_or_else(|| ApiError::InvalidInput("credential has no subject".to_string()))?;
+    if let (Some(sub), Some(subject)) = (claims.get("sub"), subject.as_object_mut()) {
+        subject.entry("id").or_insert_with(|| sub.clone());
+    }
+    let subject = Subject::from_json_value(subject)
+        .map_err(|e| ApiError::InvalidInput(format!("invalid credential subject: {}", e)))?;
+    match KnownSubject::decode(&types, &subject) {
+        Some(subject) => Ok(subject?),
+        None => Err(ApiError::InvalidInput(format!("credential of type {:?} is not part of a chain of custody", types))),
+    }
+}
+
+// Origin certificates must come from the manufacturer or an issuer trusted for them; handovers may come
+// from anyone, as the chain of custody decides whether they were signed by the right party
+struct RetailerRules<'a> {
+    policy: &'a VerifierPolicy,
+    manufacturer: Option<String>,
+    trust_registry: TrustRegistry,
+}
+
+impl VerifierRules for RetailerRules<'_> {
+    fn challenge(&self) -> &str {
+        RETAILER_CHALLENGE
+    }
+
+    fn audience(&self) -> Option<&str> {
+        Some(self.policy.audience())
+    }
+
+    fn max_presentation_lifetime_minutes(&self) -> Option<i64> {
+        self.policy.max_presentation_lifetime_minutes()
+    }
+
+    // Handovers name later custodians as subjects, so the holder is checked against the chain instead
+    fn holder_binding(&self, _credential_types: &[String]) -> HolderBinding {
+        HolderBinding::Any
+    }
+
+    fn trust(&self, credential_types: &[String], issuer: &CoreDID) -> Result<String, String> {
+        let has_type = |expected: &str| credential_types.iter().any(|credential_type| credential_type == expected);
+        if has_type(ProductOriginSubject::CREDENTIAL_TYPE) {
+            if self.manufacturer.as_deref() == Some(issuer.as_str()) {
+                Ok(format!("{} is the manufacturer", issuer))
+            } else if self.trust_registry.is_trusted(credential_types, issuer) {
+                Ok(format!("{} is trusted for {:?}", issuer, credential_types))
+            } else {
+                Err(format!("{} is neither the manufacturer nor trusted for {}", issuer, ProductOriginSubject::CREDENTIAL_TYPE))
+            }
+        } else if has_type(CustodyHandoverSubject::CREDENTIAL_TYPE) {
+            Ok(format!("{} is checked against the chain of custody", issuer))
+        } else {
+            Err(format!("{:?} is not part of a chain of custody", credential_types))
+        }
+    }
+}
+
+// DID of the manufacturer this backend runs, if it has created one
+fn manufacturer_did() -> Result<Option<String>, ApiError> {
+    let manufacturer = IdentityFiles::named("manufacturer")?;
+    if !manufacturer.exists() {
+        return Ok(None);
+    }
+    Ok(Some(load_existing_did(&manufacturer.doc_file, "manufacturer")?.id().to_string()))
+}
+
+// Checks the certified product and that its chain of custody runs without gaps to whoever presented it
+fn check_custody(body: &RequestBody, report: &mut VerificationReport) {
+    // 1. Sort the verified credentials into the origin certificate and the handovers
+    let mut origins: Vec<ProductOriginSubject> = Vec::new();
+    let mut handovers: Vec<Handover> = Vec::new();
+    for credential in &report.credentials {
+        match &credential.subject {
+            Some(KnownSubject::ProductOrigin(origin)) => origins.push(origin.clone()),
+            Some(KnownSubject::CustodyHandover(handover)) => handovers.push(Handover {
+                issuer: credential.issuer.clone(),
+                subject: handover.clone(),
+            }),
+            _ => {}
+        }
+    }
+    let origin = match origins.as_slice() {
+        [origin] => origin.clone(),
+        [] => {
+            report.fail(Check::CustodyChain, None, ErrorCode::BrokenCustodyChain, "the presentation contains no origin certificate");
+            return;
+        }
+        _ => {
+            report.fail(Check::CustodyChain, None, ErrorCode::BrokenCustodyChain, "the presentation contains more than one origin certificate");
+            return;
+        }
+    };
+
+    // 2. The certificate must be for the product the retailer expects, from the expected factory
+    if origin.product_id != body.package_id {
+        report.fail(
+            Check::Product,
+            None,
+            ErrorCode::ProductMismatch,
+            format!("the origin certificate is for {}, expected {}", origin.product_id, body.package_id),
+        );
+        return;
+    }
+    report.pass(Check::Product, None, format!("{} made at {} on {}", origin.product_id, origin.factory_origin, origin.production_date));
+    if let Some(expected_factory_origin) = &body.expected_factory_origin {
+        if &origin.factory_origin != expected_factory_origin {
+            report.fail(
+                Check::Product,
+                None,
+                ErrorCode::ProductMismatch,
+                format!("product {} was made at {}, expected {}", origin.product_id, origin.factory_origin, expected_factory_origin),
+            );
+            return;
+        }
+        report.pass(Check::Product, None, format!("factory origin matches {}", expected_factory_origin));
+    }
+
+    // 3. Each handover must be signed by the custodian at the time, ending with whoever presented the chain
+    let holder = report.holder.clone().unwrap_or_default();
+    match verify_chain(&origin, &handovers, &holder) {
+        Ok(custodians) => report.pass(Check::CustodyChain, None, format!("{} is complete: {}", origin.product_id, custodians.join(" → "))),
+        Err(e) => report.fail(Check::CustodyChain, None, ErrorCode::BrokenCustodyChain, format!("{} is broken: {}", origin.product_id, e)),
+    }
+}
+
+// --- API Handlers ---
+
+/// STEP 1: Logistics Provider (Holder) initializes their identity
+async fn logistics_provider_create_did() -> Result<Json<ApiResponse>, ApiError> {
+    let (doc, _) = create_or_load_identity(&IdentityFiles::named("logistics")?).await?;
+    Ok(ApiResponse::did(doc.id().to_string()))
+}
+
+/// STEP 2: Manufacturer (Issuer) issues a "Product Origin" VC to the Logistics Provider
+async fn manufacturer_issue_origin_vc(
+    State(state): State<Arc<SupplyChainState>>,
+    Json(body): Json<RequestBody>,
+) -> Result<Json<ApiResponse>, ApiError> {
+    // 1. Setup Manufacturer (Issuer)
+    let manufacturer = IdentityFiles::named("manufacturer")?;
+    let (issuer_doc, issuer_fragment) = create_or_load_identity(&manufacturer).await?;
+
+    // 2. Load Logistics Provider (Holder) DID
+    let holder_doc = load_existing_did(&IdentityFiles::named("logistics")?.doc_file, "logistics provider")?;
+
+    // 3. Build Credential Subject (The package details) and reject it if it does not match the schema
+    let origin = ProductOriginSubject::builder()
+        .id(holder_doc.id().as_str())
+        .product_id(body.package_id)
+        .factory_origin("Berlin_Smart_Factory_01")
+        .build()?;
+    let schema = state.product_origin_schema()?;
+    let subject_json = serde_json::to_value(&origin).map_err(ApiError::identity)?;
+    schema.validate(&subject_json).map_err(|violations| {
+        ApiError::InvalidInput(format!("subject does not conform to {}: {}", schema.id(), violations.join("; ")))
+    })?;
+
+    let credential = build_credential(
+        credential_builder("https://supply-chain.iota/credentials/origin-123", &issuer_doc, &origin)?.schema(schema.reference()),
+    )?;
+
+    // 4. Sign VC
+    let vault = open_vault(&manufacturer.stronghold_path).await?;
+    let credential_jwt = sign_credential(&issuer_doc, &vault.storage, &issuer_fragment, &credential).await?;
+
+    Ok(ApiResponse::jwt(credential_jwt))
+}
+
+/// Any party (e.g. "carrier-1") initializes its identity to take part in the chain of custody
+async fn party_create_did(Path(name): Path<String>) -> Result<Json<ApiResponse>, ApiError> {
+    let (doc, _) = create_or_load_identity(&IdentityFiles::named(&name)?).await?;
+    Ok(ApiResponse::did(doc.id().to_string()))
+}
+
+/// STEP 2b: The current custodian hands the product over to the next party
+async fn custodian_handover(Json(body): Json<HandoverBody>) -> Result<Json<ApiResponse>, ApiError> {
+    // 1. Load both parties; the sender signs, the receiver must already have a DID
+    let sender = IdentityFiles::named(&body.from)?;
+    let (sender_doc, sender_fragment) = create_or_load_identity(&sender).await?;
+    let receiver_doc = load_existing_did(&IdentityFiles::named(&body.to)?.doc_file, &body.to)?;
+
+    // 2. Work out the product, the current custodian and the next sequence number from the chain so far
+    let mut product_id = None;
+    let mut custodian = None;
+    let mut sequence = 1;
+    for jwt in &body.vc_jwts {
+        match decode_chain_subject(jwt)? {
+            KnownSubject::ProductOrigin(origin) => {
+                product_id = Some(origin.product_id);
+                custodian = Some(origin.id);
+            }
+            KnownSubject::CustodyHandover(handover) => {
+                sequence = handover.sequence + 1;
+                custodian = Some(handover.id);
+            }
+            _ => return Err(ApiError::InvalidInput("the chain may only hold origin and handover credentials".to_string())),
+        }
+    }
+    let product_id = product_id.ok_or_else(|| ApiError::InvalidInput("the chain has no origin certificate".to_string()))?;
+    if custodian.as_deref() != Some(sender_doc.id().as_str()) {
+        // Only the party currently holding the product can hand it over
+        return Err(ApiError::Conflict(format!("{} does not hold product {}", body.from, product_id)));
+    }
+
+    // 3. Build the handover credential, issued by the sender to the receiver
+    let handover = CustodyHandoverSubject::builder()
+        .id(receiver_doc.id().as_str())
+        .product_id(&product_id)
+        .previous_custodian(sender_doc.id().as_str())
+        .sequence(sequence)
+        .build()?;
+    let credential_id = format!("https://supply-chain.iota/credentials/{}/handover-{}", product_id, sequence);
+    let credential = build_credential(credential_builder(&credential_id, &sender_doc, &handover)?)?;
+
+    // 4. Sign VC
+    let vault = open_vault(&sender.stronghold_path).await?;
+    let credential_jwt = sign_credential(&sender_doc, &vault.storage, &sender_fragment, &credential).await?;
+
+    Ok(ApiResponse::jwt(credential_jwt))
+}
+
+/// Sensors and carriers report shipment events as credentials signed with their own DIDs
+async fn report_shipment_event(Json(body): Json<EventBody>) -> Result<Json<ApiResponse>, ApiError> {
+    // 1. Load the reporter's identity (created through /parties/:name/create-did)
+    let reporter = IdentityFiles::named(&body.reporter)?;
+    if !reporter.exists() {
+        return Err(ApiError::NotFound(format!("{} DID has not been created yet", body.reporter)));
+    }
+    let (reporter_doc, reporter_fragment) = create_or_load_identity(&reporter).await?;
+
+    // 2. Build the event credential; the subject is the shipment rather than a DID
+    let event = ShipmentEventSubject::new(body.product_id, body.event)?;
+    let credential_id = format!(
+        "https://supply-chain.iota/credentials/{}/events/{}",
+        event.product_id,
+        event.occurred_at.to_unix()
+    );
+    let credential = build_credential(credential_builder(&credential_id, &reporter_doc, &event)?)?;
+
+    // 3. Sign VC
+    let vault = open_vault(&reporter.stronghold_path).await?;
+    let credential_jwt = sign_credential(&reporter_doc, &vault.storage, &reporter_fragment, &credential).await?;
+
+    Ok(ApiResponse::jwt(credential_jwt))
+}
+
+/// STEP 3: The current custodian creates a Presentation of the chain for the Retailer
+async fn logistics_create_delivery_vp(
+    State(state): State<Arc<SupplyChainState>>,
+    Json(body): Json<RequestBody>,
+) -> Result<Json<ApiResponse>, ApiError> {
+    let mut vc_jwts: Vec<Jwt> = body.vc_jwt.into_iter().map(Jwt::new).collect();
+    vc_jwts.extend(body.vc_jwts.unwrap_or_default().into_iter().map(Jwt::new));
+
+    let holder = IdentityFiles::named(body.holder.as_deref().unwrap_or("logistics"))?;
+    let (holder_doc, holder_fragment) = create_or_load_identity(&holder).await?;
+    let vault = open_vault(&holder.stronghold_path).await?;
+
+    // Presentation metadata: how long it is valid and which verifier it is addressed to
+    let expires_in_minutes = body.expires_in_minutes.unwrap_or(30);
+    if expires_in_minutes <= 0 {
+        return Err(ApiError::InvalidInput("expiresInMinutes must be positive".to_string()));
+    }
+    let expires = Timestamp::now_utc()
+        .checked_add(Duration::minutes(expires_in_minutes))
+        .ok_or_else(|| ApiError::InvalidInput("expiresInMinutes is too large".to_string()))?;
+    let audience = body.audience.as_deref().unwrap_or(state.verifier_policy.audience());
+    let audience = Url::parse(audience)
+        .map_err(|e| ApiError::InvalidInput(format!("invalid audience {}: {}", audience, e)))?;
+
+    // Build and Sign VP
+    let vp_jwt = create_presentation(
+        &holder_doc,
+        &vault.storage,
+        &holder_fragment,
+        vc_jwts,
+        RETAILER_CHALLENGE,
+        &JwtPresentationOptions::default().expiration_date(expires).audience(audience),
+    )
+    .await?;
+
+    Ok(ApiResponse::jwt(vp_jwt))
+}
+
+/// STEP 4: Retailer (Verifier) validates the package origin and its chain of custody
+async fn retailer_verify_delivery(
+    State(state): State<Arc<SupplyChainState>>,
+    Json(body): Json<RequestBody>,
+) -> Result<(StatusCode, Json<VerificationReport>), ApiError> {
+    let vp_jwt = Jwt::new(body.vp_jwt.clone().ok_or_else(|| ApiError::InvalidInput("vpJwt is required".to_string()))?);
+    let mut report = VerificationReport::new();
+
+    // 1. Verify the VP and every credential in it
+    let rules = RetailerRules {
+        policy: &state.verifier_policy,
+        manufacturer: manufacturer_did()?,
+        trust_registry: state.trust_registry.read().await.clone(),
+    };
+    let options = VerifyOptions {
+        collect_all_errors: false,
+        force_fresh: body.force_fresh,
+    };
+    if let Err(status) = verify_presentation(&vp_jwt, &rules, &state.schemas, &state.did_cache, options, &mut report).await {
+        return Ok((status, Json(report.finish())));
+    }
+
+    // 2. Check the certified product and its chain of custody
+    if !report.has_failures() {
+        check_custody(&body, &mut report);
+    }
+
+    Ok((StatusCode::OK, Json(report.finish())))
+}
+
+/// Retailer (Verifier) verifies the shipment events of a product and orders them into a timeline
+async fn retailer_event_timeline(
+    State(state): State<Arc<SupplyChainState>>,
+    Json(body): Json<TimelineBody>,
+) -> Result<Json<TimelineResponse>, ApiError> {
+    let verifier = Verifier::new().with_cache(state.did_cache.clone(), body.force_fresh);
+    let mut events: Vec<(ShipmentEventSubject, String)> = Vec::new();
+    let mut rejected: Vec<String> = Vec::new();
+
+    // 1. Verify each event against the DID of the sensor or carrier that signed it.
+    // Anyone can sign an event, so the reporter must also be trusted to report shipment events
+    for (index, event_jwt) in body.event_jwts.iter().enumerate() {
+        let verified = match verifier.verify_credential(&Jwt::new(event_jwt.clone())).await {
+            Ok(verified) => verified,
+            Err(e) => {
+                rejected.push(format!("event [{}]: {}", index + 1, e));
+                continue;
+            }
+        };
+        if !state.trust_registry.read().await.is_trusted(&verified.credential.types, &verified.issuer) {
+            rejected.push(format!(
+                "event [{}]: reporter {} is not trusted for {}",
+                index + 1,
+                verified.issuer,
+                ShipmentEventSubject::CREDENTIAL_TYPE
+            ));
+            continue;
+        }
+
+        let subject = verified.credential.credential_subject.iter().next();
+        match subject.map(ShipmentEventSubject::from_subject) {
+            Some(Ok(event)) if event.product_id == body.product_id => events.push((event, verified.issuer.to_string())),
+            Some(Ok(event)) => rejected.push(format!("event [{}]: concerns product {}, not {}", index + 1, event.product_id, body.product_id)),
+            Some(Err(e)) => rejected.push(format!("event [{}]: not a shipment event: {}", index + 1, e)),
+            None => rejected.push(format!("event [{}]: has no subject", index + 1)),
+        }
+    }
+
+    // 2. Order the verified events by when they occurred
+    events.sort_by_key(|(event, _)| event.occurred_at);
+    let intact = events.iter().all(|(event, _)| match event.event {
+        ShipmentEvent::TemperatureExcursion { .. } => false,
+        ShipmentEvent::TamperSealCheck { intact, .. } => intact,
+        ShipmentEvent::Pickup { .. } | ShipmentEvent::DropOff { .. } => true,
+    });
+
+    Ok(Json(TimelineResponse {
+        product_id: body.product_id,
+        events: events
+            .into_iter()
+            .map(|(event, reporter)| TimelineEntry {
+                occurred_at: event.occurred_at.to_rfc3339(),
+                event_type: event.event_type().to_string(),
+                reporter,
+                event: event.event,
+            })
+            .collect(),
+        rejected,
+        intact,
+    }))
+}
+
+/// Routes of the supply-chain scenario, all under `/api/supply-chain`.
+pub(crate) fn router(state: Arc<SupplyChainState>) -> Router {
+    Router::new()
+        .route("/api/supply-chain/logistics/create-did", post(logistics_provider_create_did))
+        .route("/api/supply-chain/parties/:name/create-did", post(party_create_did))
+        .route("/api/supply-chain/manufacturer/issue", post(manufacturer_issue_origin_vc))
+        .route("/api/supply-chain/custody/handover", post(custodian_handover))
+        .route("/api/supply-chain/events", post(report_shipment_event))
+        .route("/api/supply-chain/logistics/present", post(logistics_create_delivery_vp))
+        .route("/api/supply-chain/retailer/verify", post(retailer_verify_delivery))
+        .route("/api/supply-chain/retailer/timeline", post(retailer_event_timeline))
+        .with_state(state)
+}
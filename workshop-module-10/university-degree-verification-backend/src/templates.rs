@@ -0,0 +1,369 @@
+use anyhow::{bail, Context, Result};
+use axum::extract::{Json, Path as UrlPath, State};
+use identity_iota::{
+    core::{Duration, Object, Timestamp, Url},
+    credential::{Credential, CredentialBuilder, Subject},
+    did::{CoreDID, DIDUrl, DID},
+    iota::IotaDocument,
+};
+use identity_logic::{
+    issuance::sign_credential,
+    store::{create_or_load_identity, load_existing_did, open_vault, IdentityFiles, VaultSession},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
+use tokio::sync::RwLock;
+
+use crate::{
+    domain_linkage::issuer_origin,
+    error::ApiError,
+    revocation::{bitmap_status, revocation_service, revocation_service_url, RevocationMechanism},
+    schema::CredentialSchema,
+    wallet::{CredentialWallet, StoredCredential},
+    AppState,
+};
+
+/// Describes how to issue one kind of credential, loaded from `templates/<name>.toml` or `.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialTemplate {
+    /// File stem the template is addressed by in `/api/issuer/:template/issue`.
+    #[serde(default)]
+    pub name: String,
+    pub credential_type: String,
+    /// JSON-LD contexts added after the base VC context.
+    #[serde(default)]
+    pub context: Vec<String>,
+    /// Credential type of the registered schema to validate subjects against, defaults to `credentialType`.
+    #[serde(default)]
+    pub schema: Option<String>,
+    #[serde(default)]
+    pub required_claims: Vec<String>,
+    #[serde(default)]
+    pub optional_claims: Vec<String>,
+    /// Days until issued credentials expire; `None` issues credentials without expiry.
+    #[serde(default)]
+    pub default_validity_days: Option<i64>,
+    #[serde(default)]
+    pub revocation: RevocationMechanism,
+}
+
+impl CredentialTemplate {
+    /// Rejects subjects that miss a required claim or carry one the template does not declare.
+    /// A template that declares no claims at all accepts any claims.
+    fn check_claims(&self, claims: &serde_json::Map<String, serde_json::Value>) -> Result<(), ApiError> {
+        let missing: Vec<&str> = self
+            .required_claims
+            .iter()
+            .filter(|claim| !claims.contains_key(claim.as_str()))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(ApiError::InvalidInput(format!("missing required claims: {}", missing.join(", "))));
+        }
+
+        if claims.contains_key("id") {
+            return Err(ApiError::InvalidInput("the subject id is set with subjectDid, not as a claim".to_string()));
+        }
+
+        if self.required_claims.is_empty() && self.optional_claims.is_empty() {
+            return Ok(());
+        }
+        let unknown: Vec<&str> = claims
+            .keys()
+            .filter(|claim| !self.required_claims.contains(claim) && !self.optional_claims.contains(claim))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            return Err(ApiError::InvalidInput(format!(
+                "claims not declared by template {}: {}",
+                self.name,
+                unknown.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.credential_type.trim().is_empty() {
+            bail!("credentialType must not be empty");
+        }
+        for context in &self.context {
+            Url::parse(context).with_context(|| format!("invalid context {}", context))?;
+        }
+        if let Some(claim) = self.required_claims.iter().find(|claim| self.optional_claims.contains(claim)) {
+            bail!("claim {} is both required and optional", claim);
+        }
+        if matches!(self.default_validity_days, Some(days) if days <= 0) {
+            bail!("defaultValidityDays must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// Credential templates keyed by name.
+#[derive(Debug, Default)]
+pub struct TemplateRegistry {
+    templates: BTreeMap<String, CredentialTemplate>,
+}
+
+impl TemplateRegistry {
+    /// Loads every `.toml` and `.json` template in `dir`; a missing directory yields no templates.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut registry = TemplateRegistry::default();
+        if !dir.exists() {
+            return Ok(registry);
+        }
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()).map(str::to_owned) else {
+                continue;
+            };
+            let mut template: CredentialTemplate = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("toml") => toml::from_str(&fs::read_to_string(&path)?)
+                    .with_context(|| format!("invalid template {}", path.display()))?,
+                Some("json") => serde_json::from_str(&fs::read_to_string(&path)?)
+                    .with_context(|| format!("invalid template {}", path.display()))?,
+                _ => continue,
+            };
+            template.name = name.clone();
+            template.validate().with_context(|| format!("invalid template {}", path.display()))?;
+
+            if registry.templates.insert(name.clone(), template).is_some() {
+                bail!("template {} is defined more than once in {}", name, dir.display());
+            }
+        }
+        Ok(registry)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CredentialTemplate> {
+        self.templates.get(name)
+    }
+
+    pub fn list(&self) -> Vec<&CredentialTemplate> {
+        self.templates.values().collect()
+    }
+}
+
+// --- Template Issuance API ---
+
+pub(crate) const ISSUER_DOC_FILE: &str = "./issuer_doc.json";
+pub(crate) const ISSUER_STRONGHOLD_PATH: &str = "./issuer.stronghold";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueFromTemplate {
+    // DID the credential is issued to; defaults to the workshop holder
+    pub(crate) subject_did: Option<String>,
+    pub(crate) claims: serde_json::Map<String, serde_json::Value>,
+    // Overrides the template's defaultValidityDays
+    pub(crate) validity_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuedCredential {
+    jwt: String,
+    credential_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revocation_index: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplatesResponse {
+    templates: Vec<CredentialTemplate>,
+}
+
+pub async fn list_templates(State(state): State<Arc<AppState>>) -> Json<TemplatesResponse> {
+    Json(TemplatesResponse {
+        templates: state.templates.list().into_iter().cloned().collect(),
+    })
+}
+
+/// Who signs credentials issued from a template, and whose wallet receives the ones issued to them.
+pub(crate) struct IssuanceParties<'a> {
+    pub(crate) issuer: IdentityFiles,
+    pub(crate) holder_doc_file: String,
+    pub(crate) holder_wallet: &'a RwLock<CredentialWallet>,
+}
+
+impl<'a> IssuanceParties<'a> {
+    /// The workshop issuer and holder of the degree scenario.
+    pub(crate) fn workshop(state: &'a AppState) -> Self {
+        IssuanceParties {
+            issuer: IdentityFiles {
+                doc_file: ISSUER_DOC_FILE.to_string(),
+                fragment_file: "./issuer_fragment.txt".to_string(),
+                stronghold_path: ISSUER_STRONGHOLD_PATH.to_string(),
+            },
+            holder_doc_file: "./holder_doc.json".to_string(),
+            holder_wallet: &state.wallet,
+        }
+    }
+}
+
+/// Issues credentials from one template on behalf of an issuer.
+pub(crate) struct TemplateIssuer<'a> {
+    state: &'a AppState,
+    template: &'a CredentialTemplate,
+    holder_wallet: &'a RwLock<CredentialWallet>,
+    schema: Option<&'a CredentialSchema>,
+    pub(crate) issuer_doc: IotaDocument,
+    issuer_fragment: String,
+    holder_did: Option<String>,
+    // Revocation service credentials point at, if the template uses a revocation bitmap
+    pub(crate) revocation_service: Option<DIDUrl>,
+}
+
+impl<'a> TemplateIssuer<'a> {
+    /// Looks up the template and loads the issuer DID. Unless `service_may_be_missing`, a template using a
+    /// revocation bitmap requires the issuer DID document to already contain the revocation service.
+    pub(crate) async fn new(state: &'a AppState, template_name: &str, service_may_be_missing: bool) -> Result<Self, ApiError> {
+        Self::with_parties(state, template_name, IssuanceParties::workshop(state), service_may_be_missing).await
+    }
+
+    /// Same as [`TemplateIssuer::new`], issuing as `parties.issuer` and delivering to `parties.holder_wallet`.
+    pub(crate) async fn with_parties(
+        state: &'a AppState,
+        template_name: &str,
+        parties: IssuanceParties<'a>,
+        service_may_be_missing: bool,
+    ) -> Result<Self, ApiError> {
+        let template = state
+            .templates
+            .get(template_name)
+            .ok_or_else(|| ApiError::NotFound(format!("template {} not found", template_name)))?;
+
+        let schema = match &template.schema {
+            Some(schema_type) => Some(state.schemas.get(schema_type).ok_or_else(|| {
+                ApiError::Configuration(anyhow::anyhow!("template {} uses unknown schema {}", template.name, schema_type))
+            })?),
+            None => state.schemas.get(&template.credential_type),
+        };
+
+        let (issuer_doc, issuer_fragment) = create_or_load_identity(&parties.issuer).await?;
+        let holder_did = load_existing_did(&parties.holder_doc_file, "holder").ok().map(|doc| doc.id().to_string());
+
+        let revocation_service = match &template.revocation {
+            RevocationMechanism::None => None,
+            RevocationMechanism::RevocationBitmap2022 { service } if service_may_be_missing => {
+                Some(revocation_service_url(&issuer_doc, service)?)
+            }
+            RevocationMechanism::RevocationBitmap2022 { service } => Some(revocation_service(&issuer_doc, service)?),
+        };
+
+        Ok(TemplateIssuer {
+            state,
+            template,
+            holder_wallet: parties.holder_wallet,
+            schema,
+            issuer_doc,
+            issuer_fragment,
+            holder_did,
+            revocation_service,
+        })
+    }
+
+    /// Checks the request against the template and its schema and returns the subject to issue to.
+    fn prepare_subject(&self, request: &IssueFromTemplate) -> Result<(String, serde_json::Value), ApiError> {
+        self.template.check_claims(&request.claims)?;
+
+        let subject_did = request
+            .subject_did
+            .clone()
+            .or_else(|| self.holder_did.clone())
+            .ok_or_else(|| ApiError::InvalidInput("subjectDid is required until the holder DID is created".to_string()))?;
+        CoreDID::parse(&subject_did)
+            .map_err(|e| ApiError::InvalidInput(format!("invalid subjectDid {}: {}", subject_did, e)))?;
+
+        let mut subject_json = request.claims.clone();
+        subject_json.insert("id".to_string(), serde_json::Value::String(subject_did.clone()));
+        let subject_json = serde_json::Value::Object(subject_json);
+
+        if let Some(schema) = self.schema {
+            schema.validate(&subject_json).map_err(|violations| {
+                ApiError::InvalidInput(format!("subject does not conform to {}: {}", schema.id(), violations.join("; ")))
+            })?;
+        }
+        Ok((subject_did, subject_json))
+    }
+
+    /// Builds and signs one credential with the already opened issuer vault.
+    pub(crate) async fn issue(&self, vault: &VaultSession, request: &IssueFromTemplate) -> Result<IssuedCredential, ApiError> {
+        // 1. Check the subject before anything is signed
+        let (subject_did, subject_json) = self.prepare_subject(request)?;
+
+        // 2. Build VC
+        let credential_id = issuer_origin()
+            .map_err(ApiError::Configuration)?
+            .join(format!("/credentials/{}", nanoid::nanoid!()))
+            .map_err(ApiError::identity)?;
+        let mut builder = CredentialBuilder::default()
+            .id(credential_id.clone())
+            .issuer(Url::parse(self.issuer_doc.id().as_str()).map_err(ApiError::identity)?)
+            .type_(self.template.credential_type.clone())
+            .subject(Subject::from_json_value(subject_json).map_err(ApiError::identity)?)
+            .issuance_date(Timestamp::now_utc());
+        for context in &self.template.context {
+            builder = builder.context(Url::parse(context).map_err(ApiError::identity)?);
+        }
+        if let Some(schema) = self.schema {
+            builder = builder.schema(schema.reference());
+        }
+
+        if let Some(days) = request.validity_days.or(self.template.default_validity_days) {
+            if days <= 0 {
+                return Err(ApiError::InvalidInput("validityDays must be positive".to_string()));
+            }
+            let expires = Timestamp::now_utc()
+                .checked_add(Duration::days(days))
+                .ok_or_else(|| ApiError::InvalidInput("validityDays is too large".to_string()))?;
+            builder = builder.expiration_date(expires);
+        }
+
+        let mut revocation_index = None;
+        if let Some(service_url) = &self.revocation_service {
+            let index = self
+                .state
+                .revocation_indices
+                .write()
+                .await
+                .allocate(service_url)
+                .map_err(ApiError::storage)?;
+            builder = builder.status(bitmap_status(service_url.clone(), index));
+            revocation_index = Some(index);
+        }
+
+        let credential: Credential<Object> = builder.build().map_err(ApiError::identity)?;
+
+        // 3. Sign VC
+        let credential_jwt = sign_credential(&self.issuer_doc, &vault.storage, &self.issuer_fragment, &credential).await?;
+
+        // 4. Deliver the VC to the holder wallet when it was issued to the holder
+        if self.holder_did.as_deref() == Some(subject_did.as_str()) {
+            let stored = StoredCredential::from_jwt(credential_jwt.as_str())?;
+            self.holder_wallet.write().await.store(stored).map_err(ApiError::storage)?;
+        }
+
+        Ok(IssuedCredential {
+            jwt: credential_jwt.as_str().to_string(),
+            credential_id: credential_id.to_string(),
+            revocation_index,
+        })
+    }
+}
+
+// Builds and signs a credential of the template's type for the given claims
+pub async fn issue_from_template(
+    State(state): State<Arc<AppState>>,
+    UrlPath(template_name): UrlPath<String>,
+    Json(body): Json<IssueFromTemplate>,
+) -> Result<Json<IssuedCredential>, ApiError> {
+    let issuer = TemplateIssuer::new(&state, &template_name, false).await?;
+    let vault = open_vault(ISSUER_STRONGHOLD_PATH).await?;
+    let issued = issuer.issue(&vault, &body).await?;
+    Ok(Json(issued))
+}
@@ -0,0 +1,20 @@
+//! Developer tools served alongside every scenario.
+
+use axum::{extract::Json, routing::post, Router};
+use identity_logic::inspect::{inspect_jwt, Inspection};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeRequest {
+    jwt: String,
+}
+
+// Decodes a VC or VP JWT without verifying it, expanding the credentials inside a VP
+pub async fn decode_jwt(Json(body): Json<DecodeRequest>) -> Json<Inspection> {
+    Json(inspect_jwt(&body.jwt))
+}
+
+pub(crate) fn router() -> Router {
+    Router::new().route("/api/tools/decode", post(decode_jwt))
+}
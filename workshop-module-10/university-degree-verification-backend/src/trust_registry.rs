@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Json, State},
+    routing::get,
+    Router,
+};
+use identity_iota::did::CoreDID;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::RwLock;
+
+use crate::error::ApiError;
+
+/// Base type carried by every credential; it never identifies what the credential attests to.
+const BASE_CREDENTIAL_TYPE: &str = "VerifiableCredential";
+
+/// Trusted issuer DIDs per credential type, persisted as JSON next to the DID documents.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TrustRegistry {
+    #[serde(skip)]
+    path: PathBuf,
+    issuers: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TrustRegistry {
+    /// Loads the registry from `path`, starting empty if the file does not exist yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut registry = if path.exists() {
+            let json = fs::read_to_string(&path)?;
+            serde_json::from_str::<TrustRegistry>(&json)
+                .with_context(|| format!("invalid trust registry {}", path.display()))?
+        } else {
+            TrustRegistry::default()
+        };
+        registry.path = path;
+        Ok(registry)
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write trust registry {}", self.path.display()))
+    }
+
+    /// Adds `issuer` as trusted for `credential_type` and persists the registry. The registry is
+    /// only changed once the update is saved.
+    pub fn trust(&mut self, credential_type: &str, issuer: &CoreDID) -> Result<()> {
+        let mut updated = self.clone();
+        updated
+            .issuers
+            .entry(credential_type.to_string())
+            .or_default()
+            .insert(issuer.to_string());
+        updated.save()?;
+        *self = updated;
+        Ok(())
+    }
+
+    /// Removes `issuer` from `credential_type` and persists the registry. Returns whether it was present.
+    /// The registry is only changed once the update is saved.
+    pub fn revoke(&mut self, credential_type: &str, issuer: &CoreDID) -> Result<bool> {
+        let mut updated = self.clone();
+        let removed = match updated.issuers.get_mut(credential_type) {
+            Some(issuers) => {
+                let removed = issuers.remove(issuer.as_str());
+                if issuers.is_empty() {
+                    updated.issuers.remove(credential_type);
+                }
+                removed
+            }
+            None => false,
+        };
+        if removed {
+            updated.save()?;
+            *self = updated;
+        }
+        Ok(removed)
+    }
+
+    /// Returns whether `issuer` is trusted for every specific type the credential declares.
+    pub fn is_trusted(&self, credential_types: &[String], issuer: &CoreDID) -> bool {
+        let mut specific_types = credential_types
+            .iter()
+            .filter(|credential_type| credential_type.as_str() != BASE_CREDENTIAL_TYPE)
+            .peekable();
+
+        specific_types.peek().is_some()
+            && specific_types.all(|credential_type| {
+                self.issuers
+                    .get(credential_type)
+                    .map_or(false, |issuers| issuers.contains(issuer.as_str()))
+            })
+    }
+
+    pub fn entries(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.issuers
+    }
+}
+
+// --- Admin API ---
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedIssuerEntry {
+    credential_type: String,
+    did: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedIssuersResponse {
+    trusted_issuers: BTreeMap<String, BTreeSet<String>>,
+}
+
+pub async fn list_trusted_issuers(State(registry): State<Arc<RwLock<TrustRegistry>>>) -> Json<TrustedIssuersResponse> {
+    let registry = registry.read().await;
+    Json(TrustedIssuersResponse {
+        trusted_issuers: registry.entries().clone(),
+    })
+}
+
+pub async fn add_trusted_issuer(
+    State(registry): State<Arc<RwLock<TrustRegistry>>>,
+    Json(body): Json<TrustedIssuerEntry>,
+) -> Result<Json<TrustedIssuersResponse>, ApiError> {
+    let issuer = CoreDID::parse(&body.did)
+        .map_err(|e| ApiError::InvalidInput(format!("invalid DID {}: {}", body.did, e)))?;
+
+    let mut registry = registry.write().await;
+    registry.trust(&body.credential_type, &issuer).map_err(ApiError::storage)?;
+
+    Ok(Json(TrustedIssuersResponse {
+        trusted_issuers: registry.entries().clone(),
+    }))
+}
+
+pub async fn remove_trusted_issuer(
+    State(registry): State<Arc<RwLock<TrustRegistry>>>,
+    Json(body): Json<TrustedIssuerEntry>,
+) -> Result<Json<TrustedIssuersResponse>, ApiError> {
+    let issuer = CoreDID::parse(&body.did)
+        .map_err(|e| ApiError::InvalidInput(format!("invalid DID {}: {}", body.did, e)))?;
+
+    let mut registry = registry.write().await;
+    let removed = registry.revoke(&body.credential_type, &issuer).map_err(ApiError::storage)?;
+    if !removed {
+        return Err(ApiError::NotFound(format!(
+            "{} is not a trusted issuer for {}",
+            issuer, body.credential_type
+        )));
+    }
+
+    Ok(Json(TrustedIssuersResponse {
+        trusted_issuers: registry.entries().clone(),
+    }))
+}
+
+/// The trusted issuer admin API, served with every scenario since all of their verifiers share the registry.
+pub(crate) fn router(registry: Arc<RwLock<TrustRegistry>>) -> Router {
+    Router::new()
+        .route(
+            "/api/admin/trusted-issuers",
+            get(list_trusted_issuers).post(add_trusted_issuer).delete(remove_trusted_issuer),
+        )
+        .with_state(registry)
+}
@@ -0,0 +1,193 @@
+use anyhow::{Context, Result};
+use axum::extract::{Json, Path as UrlPath, State};
+use identity_iota::core::Timestamp;
+use identity_logic::inspect::decode_claims;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{error::ApiError, AppState};
+
+/// A credential kept by the holder, with metadata decoded from its JWT claims.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredCredential {
+    pub id: String,
+    pub jwt: String,
+    pub issuer: String,
+    pub types: Vec<String>,
+    pub subject: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuance_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_date: Option<String>,
+    pub received_at: String,
+}
+
+impl StoredCredential {
+    /// Decodes the VC JWT claims without verifying the signature; the verifier does that later.
+    pub fn from_jwt(jwt: &str) -> Result<Self, ApiError> {
+        let claims = decode_jwt_claims(jwt)?;
+        let vc = claims
+            .get("vc")
+            .ok_or_else(|| ApiError::InvalidInput("JWT carries no `vc` claim".to_string()))?;
+
+        let issuer = claims
+            .get("iss")
+            .and_then(|iss| iss.as_str())
+            .ok_or_else(|| ApiError::InvalidInput("JWT carries no `iss` claim".to_string()))?
+            .to_string();
+
+        let types = credential_types(&claims);
+
+        let mut subject = vc.get("credentialSubject").cloned().unwrap_or_default();
+        if let (Some(sub), Some(subject)) = (claims.get("sub"), subject.as_object_mut()) {
+            subject.entry("id").or_insert_with(|| sub.clone());
+        }
+
+        let timestamp = |claim: &str| {
+            claims
+                .get(claim)
+                .and_then(|seconds| seconds.as_i64())
+                .and_then(|seconds| Timestamp::from_unix(seconds).ok())
+                .map(|timestamp| timestamp.to_rfc3339())
+        };
+
+        Ok(StoredCredential {
+            id: nanoid::nanoid!(),
+            jwt: jwt.to_string(),
+            issuer,
+            types,
+            subject,
+            issuance_date: timestamp("nbf"),
+            expiration_date: timestamp("exp"),
+            received_at: Timestamp::now_utc().to_rfc3339(),
+        })
+    }
+}
+
+/// Returns the `vc.type` entries of decoded VC JWT claims.
+pub fn credential_types(claims: &serde_json::Value) -> Vec<String> {
+    match claims.get("vc").and_then(|vc| vc.get("type")) {
+        Some(serde_json::Value::String(credential_type)) => vec![credential_type.clone()],
+        Some(serde_json::Value::Array(credential_types)) => credential_types
+            .iter()
+            .filter_map(|credential_type| credential_type.as_str().map(str::to_owned))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Decodes the claims of a JWT without checking its signature, the same way JWT inspection does.
+pub fn decode_jwt_claims(jwt: &str) -> Result<serde_json::Value, ApiError> {
+    decode_claims(jwt).map_err(|e| ApiError::InvalidInput(format!("invalid JWT: {}", e)))
+}
+
+/// The holder's credentials, persisted as JSON next to the holder DID document.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CredentialWallet {
+    #[serde(skip)]
+    path: PathBuf,
+    credentials: Vec<StoredCredential>,
+}
+
+impl CredentialWallet {
+    /// Loads the wallet from `path`, starting empty if the file does not exist yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut wallet = if path.exists() {
+            let json = fs::read_to_string(&path)?;
+            serde_json::from_str::<CredentialWallet>(&json)
+                .with_context(|| format!("invalid credential wallet {}", path.display()))?
+        } else {
+            CredentialWallet::default()
+        };
+        wallet.path = path;
+        Ok(wallet)
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write credential wallet {}", self.path.display()))
+    }
+
+    /// Stores a credential and persists the wallet. A JWT already in the wallet is not stored twice.
+    /// The wallet is only changed once the update is saved.
+    pub fn store(&mut self, credential: StoredCredential) -> Result<StoredCredential> {
+        if let Some(existing) = self.credentials.iter().find(|stored| stored.jwt == credential.jwt) {
+            return Ok(existing.clone());
+        }
+        let mut updated = self.clone();
+        updated.credentials.push(credential.clone());
+        updated.save()?;
+        *self = updated;
+        Ok(credential)
+    }
+
+    /// Removes a credential and persists the wallet. Returns whether it was present.
+    /// The wallet is only changed once the update is saved.
+    pub fn remove(&mut self, id: &str) -> Result<bool> {
+        let mut updated = self.clone();
+        updated.credentials.retain(|stored| stored.id != id);
+        if updated.credentials.len() == self.credentials.len() {
+            return Ok(false);
+        }
+        updated.save()?;
+        *self = updated;
+        Ok(true)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&StoredCredential> {
+        self.credentials.iter().find(|stored| stored.id == id)
+    }
+
+    pub fn list(&self) -> &[StoredCredential] {
+        &self.credentials
+    }
+}
+
+// --- Holder Wallet API ---
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreCredentialRequest {
+    vc_jwt: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialsResponse {
+    credentials: Vec<StoredCredential>,
+}
+
+pub async fn list_credentials(State(state): State<Arc<AppState>>) -> Json<CredentialsResponse> {
+    let wallet = state.wallet.read().await;
+    Json(CredentialsResponse {
+        credentials: wallet.list().to_vec(),
+    })
+}
+
+pub async fn store_credential(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<StoreCredentialRequest>,
+) -> Result<Json<StoredCredential>, ApiError> {
+    let credential = StoredCredential::from_jwt(&body.vc_jwt)?;
+    let stored = state.wallet.write().await.store(credential).map_err(ApiError::storage)?;
+    Ok(Json(stored))
+}
+
+pub async fn delete_credential(
+    State(state): State<Arc<AppState>>,
+    UrlPath(id): UrlPath<String>,
+) -> Result<Json<CredentialsResponse>, ApiError> {
+    let mut wallet = state.wallet.write().await;
+    if !wallet.remove(&id).map_err(ApiError::storage)? {
+        return Err(ApiError::NotFound(format!("credential {} not found", id)));
+    }
+    Ok(Json(CredentialsResponse {
+        credentials: wallet.list().to_vec(),
+    }))
+}